@@ -0,0 +1,166 @@
+// ===== 表示メッセージの翻訳（i18n feature） =====
+//
+// 各バイナリが Fluent（.ftl）のカタログを include_str! で埋め込み、起動時に init で登録する。
+// t! で引くと 今の言語 → 英語 → メッセージ ID の順に探す。
+// API のエラーメッセージ（ErrorBody::message）は翻訳しない（相手は code で判断する）。
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::FluentResource;
+use serde::{Deserialize, Serialize};
+use std::sync::{PoisonError, RwLock};
+
+pub use fluent_bundle::{FluentArgs, FluentValue};
+
+/// 表示する言語
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Ja];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Ja => "ja",
+        }
+    }
+
+    /// 言語の選択肢に出す名前（その言語で書く）
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Ja => "日本語",
+        }
+    }
+
+    /// "ja"、"ja-JP"、"ja_JP.UTF-8" などを受け付ける
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let lang = tag.split(['-', '_', '.', '@']).next()?;
+        Locale::ALL
+            .into_iter()
+            .find(|l| l.as_str().eq_ignore_ascii_case(lang))
+    }
+
+    /// BATTLE_LANG → LC_ALL → LC_MESSAGES → LANG の順に最初の空でない値を見る
+    /// （知らない言語・"C" などなら英語）
+    pub fn from_env() -> Locale {
+        ["BATTLE_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|key| std::env::var(key).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::parse(&value))
+            .unwrap_or_default()
+    }
+}
+
+impl std::str::FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Locale::parse(s).ok_or_else(|| format!("unknown language '{}' (en / ja)", s))
+    }
+}
+
+/// 言語ごとの .ftl の中身
+pub type Catalog = &'static [(Locale, &'static str)];
+
+type Bundle = FluentBundle<FluentResource>;
+
+struct Localizer {
+    catalog: Catalog,
+    locale: Locale,
+    bundle: Option<Bundle>,
+    fallback: Option<Bundle>, // 英語
+}
+
+static LOCALIZER: RwLock<Option<Localizer>> = RwLock::new(None);
+
+fn build(catalog: Catalog, locale: Locale) -> Option<Bundle> {
+    let (_, source) = catalog.iter().find(|(l, _)| *l == locale)?;
+    // カタログは埋め込みなので、壊れていても読めた分だけ使う
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(res, errors)| {
+        eprintln!("{}.ftl: {} syntax error(s)", locale.as_str(), errors.len());
+        res
+    });
+    let langid = locale.as_str().parse().ok()?;
+    let mut bundle = Bundle::new_concurrent(vec![langid]);
+    // 端末や egui では双方向テキスト用の制御文字が豆腐になる
+    bundle.set_use_isolating(false);
+    if let Err(errors) = bundle.add_resource(resource) {
+        eprintln!(
+            "{}.ftl: {} duplicate message(s)",
+            locale.as_str(),
+            errors.len()
+        );
+    }
+    Some(bundle)
+}
+
+/// 起動時に一度だけ呼ぶ（もう一度呼ぶとカタログごと差し替える）
+pub fn init(catalog: Catalog, locale: Locale) {
+    let localizer = Localizer {
+        catalog,
+        locale,
+        bundle: build(catalog, locale),
+        fallback: build(catalog, Locale::En),
+    };
+    *LOCALIZER.write().unwrap_or_else(PoisonError::into_inner) = Some(localizer);
+}
+
+/// 表示中の言語を切り替える（init の前なら何もしない）
+pub fn set_locale(locale: Locale) {
+    let mut guard = LOCALIZER.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(localizer) = guard.as_mut() {
+        if localizer.locale != locale {
+            localizer.bundle = build(localizer.catalog, locale);
+            localizer.locale = locale;
+        }
+    }
+}
+
+pub fn locale() -> Locale {
+    LOCALIZER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map(|l| l.locale)
+        .unwrap_or_default()
+}
+
+/// t! から呼ぶ。見つからなければメッセージ ID をそのまま返す
+pub fn tr(id: &str, args: Option<&FluentArgs>) -> String {
+    let guard = LOCALIZER.read().unwrap_or_else(PoisonError::into_inner);
+    let Some(localizer) = guard.as_ref() else {
+        return id.to_string();
+    };
+    for bundle in [&localizer.bundle, &localizer.fallback]
+        .into_iter()
+        .flatten()
+    {
+        if let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) {
+            let mut errors = Vec::new();
+            return bundle
+                .format_pattern(pattern, args, &mut errors)
+                .into_owned();
+        }
+    }
+    id.to_string()
+}
+
+/// `t!("lobby-joined", name = name, players = 3)` のように引数を名前付きで渡す
+#[macro_export]
+macro_rules! t {
+    ($id:expr) => {
+        $crate::i18n::tr($id, None)
+    };
+    ($id:expr, $($key:ident = $value:expr),+ $(,)?) => {{
+        let mut args = $crate::i18n::FluentArgs::new();
+        $(args.set(stringify!($key), $crate::i18n::FluentValue::from($value));)+
+        $crate::i18n::tr($id, Some(&args))
+    }};
+}
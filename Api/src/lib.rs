@@ -0,0 +1,689 @@
+// battle_server と battle_client_gui の間でやり取りする型
+//
+// サーバのレスポンスとクライアントのパース先を同じ定義にして、
+// 片方だけ変更してずれることを防ぐ。
+//
+// 互換性のルール:
+// - 知らないフィールドは無視する（deny_unknown_fields は付けない）
+// - 後から足すフィールドは Option か #[serde(default)] にして、古い相手からの省略を許す
+// - レスポンスに出る enum には #[serde(other)] の Unknown を置き、値を足しても古い相手が読めるようにする
+//   （Unknown はリクエストでは使わない。サーバは入力チェックで断る）
+// - それ以外の変更（型・意味の変更や削除）をしたら PROTOCOL_VERSION を上げる
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+pub mod engine;
+#[cfg(feature = "i18n")]
+pub mod i18n;
+pub mod roster;
+
+/// すべての API のパスの先頭
+pub const API_PREFIX: &str = "/v1";
+
+// ===== プロトコルのバージョン =====
+
+/// このビルドが話すプロトコルのバージョン
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// クライアントは自分の PROTOCOL_VERSION を、サーバは自分のものをこのヘッダで送る
+pub const PROTOCOL_HEADER: &str = "x-battle-protocol";
+
+/// ServerInfo::capabilities に入る値
+pub mod capability {
+    /// /v1/join, /v1/tickets, /v1/matches, /v1/register, /v1/login
+    pub const MATCHMAKING: &str = "matchmaking";
+    /// /v1/battle
+    pub const INSTANT: &str = "instant";
+    /// /v1/battle/roster
+    pub const ROSTER: &str = "roster";
+    /// /openapi.json
+    pub const OPENAPI: &str = "openapi";
+    /// Accept: application/msgpack でレスポンスを MessagePack で返せる
+    pub const MSGPACK: &str = "msgpack";
+    /// Accept: application/cbor でレスポンスを CBOR で返せる
+    pub const CBOR: &str = "cbor";
+    /// /v1/matches/{id}/log
+    pub const BATTLE_LOG: &str = "battle_log";
+    /// /v1/lobbies と JoinRequest の mode / lobby
+    pub const LOBBIES: &str = "lobbies";
+    /// JoinRequest::squad（1回の参加で複数のキャラクター）
+    pub const SQUAD: &str = "squad";
+    /// BattleLog の seed と狙い方（engine::verify で結果を再計算できる）
+    pub const VERIFY: &str = "verify";
+}
+
+/// レスポンスの形式（Accept / Content-Type）。エラーボディは常に JSON
+pub mod mime {
+    pub const JSON: &str = "application/json";
+    pub const MSGPACK: &str = "application/msgpack";
+    pub const CBOR: &str = "application/cbor";
+}
+
+/// LAN 内の発見（サーバ・クライアントとも mdns feature のときだけ使う）
+pub mod mdns {
+    /// HTTP ポートをこのサービス種別で広告する
+    pub const SERVICE_TYPE: &str = "_battle._tcp.local.";
+    /// TXT レコードのキー
+    pub const TXT_VERSION: &str = "version";
+    pub const TXT_PROTOCOL: &str = "protocol";
+}
+
+/// GET /v1/version（プロトコルのバージョンに関係なく呼べる）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ServerInfo {
+    pub server_version: String,
+    pub protocol_version: u32,
+    /// これより古いクライアントは client_too_old（426）で断られる
+    pub min_protocol_version: u32,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// /v1/join で使えるステータスの予算（古いサーバは送らない）
+    #[serde(default)]
+    pub stat_budget: Option<StatBudget>,
+}
+
+impl ServerInfo {
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+}
+
+// ===== エラー =====
+
+/// 全エンドポイント共通のエラーボディ
+///
+/// ```json
+/// { "code": "invalid_request", "message": "name is empty", "details": null }
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
+    pub details: Option<Value>,
+}
+
+// ===== アカウント（/v1/register, /v1/login） =====
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct CredentialsRequest {
+    pub name: String,
+    pub password: String,
+}
+
+/// クライアントはそのまま保存してログイン状態を復元する
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct TokenResponse {
+    pub name: String,
+    pub token: String,
+    pub expires_at: u64, // UNIX 秒
+}
+
+impl TokenResponse {
+    pub fn is_expired(&self) -> bool {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.expires_at <= now
+    }
+}
+
+// ===== キャラクター =====
+
+/// クラスごとに基本ステータスへ倍率をかける
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum CharacterClass {
+    /// 補正なし
+    #[default]
+    Fighter,
+    /// hp 1.25 倍 / atk 0.8 倍
+    Tank,
+    /// hp 0.8 倍 / atk 1.25 倍
+    Striker,
+    /// このビルドが知らないクラス（新しいサーバの結果を読むとき用）。補正は fighter と同じ
+    #[serde(other)]
+    Unknown,
+}
+
+impl CharacterClass {
+    pub const ALL: [CharacterClass; 3] = [
+        CharacterClass::Fighter,
+        CharacterClass::Tank,
+        CharacterClass::Striker,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CharacterClass::Fighter => "fighter",
+            CharacterClass::Tank => "tank",
+            CharacterClass::Striker => "striker",
+            CharacterClass::Unknown => "unknown",
+        }
+    }
+
+    /// 基本ステータスから実際に戦うときの (hp, atk) を出す
+    pub fn derive_stats(self, hp: i32, atk: i32) -> (i32, i32) {
+        let (hp_pct, atk_pct) = match self {
+            CharacterClass::Fighter | CharacterClass::Unknown => (100, 100),
+            CharacterClass::Tank => (125, 80),
+            CharacterClass::Striker => (80, 125),
+        };
+        // i32 に収まらない分は切り詰める。hp は 1 未満にはしない（atk 0 はそのまま）
+        let scale = |value: i32, pct: i64| {
+            (value as i64 * pct / 100).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+        };
+        (scale(hp, hp_pct).max(1), scale(atk, atk_pct))
+    }
+}
+
+/// 攻撃するときの相手の選び方
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Targeting {
+    /// 生存者から一様に選ぶ
+    #[default]
+    Random,
+    /// 残り hp が一番少ない相手
+    Weakest,
+    /// atk が一番高い相手
+    Strongest,
+    /// このビルドが知らない狙い方（新しいサーバのログを読むとき用）。Random として扱う
+    #[serde(other)]
+    Unknown,
+}
+
+impl Targeting {
+    pub const ALL: [Targeting; 3] = [Targeting::Random, Targeting::Weakest, Targeting::Strongest];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Targeting::Random => "random",
+            Targeting::Weakest => "weakest",
+            Targeting::Strongest => "strongest",
+            Targeting::Unknown => "unknown",
+        }
+    }
+}
+
+/// バトルに出せる hp / atk の上限（サーバの入力チェックで使う）
+///
+/// hp が大きく atk が小さいキャラばかりだとバトルがいつまでも終わらないので抑えておく
+pub const MAX_STAT: i32 = 10_000;
+
+/// 基本値に振り分けポイントを足してステータスを作る（クラス補正の前）
+///
+/// hp = base_hp + hp_points * hp_per_point, atk = base_atk + atk_points * atk_per_point
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct StatBudget {
+    /// hp と atk に振り分けられるポイントの合計
+    pub points: i32,
+    pub base_hp: i32,
+    pub base_atk: i32,
+    pub hp_per_point: i32,
+    pub atk_per_point: i32,
+}
+
+impl Default for StatBudget {
+    /// 予算導入前のクライアントの乱数（hp 80..120 / atk 5..20）がそのまま収まる値
+    fn default() -> Self {
+        Self {
+            points: 40,
+            base_hp: 80,
+            base_atk: 5,
+            hp_per_point: 2,
+            atk_per_point: 1,
+        }
+    }
+}
+
+impl StatBudget {
+    pub fn stats(&self, hp_points: i32, atk_points: i32) -> (i32, i32) {
+        let stat =
+            |base: i32, points: i32, per: i32| base.saturating_add(points.saturating_mul(per));
+        (
+            stat(self.base_hp, hp_points, self.hp_per_point),
+            stat(self.base_atk, atk_points, self.atk_per_point),
+        )
+    }
+
+    /// (hp, atk) を作るのに必要なポイント（基本値より低ければ None）
+    ///
+    /// i32 に収まらないほど大きければ i32::MAX（どの予算でも超える扱い）
+    pub fn cost(&self, hp: i32, atk: i32) -> Option<i32> {
+        if hp < self.base_hp || atk < self.base_atk {
+            return None;
+        }
+        // 差は i32 を超えることがあるので i64 で取り、端数は切り上げる
+        let per_point = |value: i32, base: i32, per: i32| {
+            let extra = (value as i64 - base as i64) as u64;
+            extra.div_ceil(per.max(1) as u64)
+        };
+        let total = per_point(hp, self.base_hp, self.hp_per_point)
+            + per_point(atk, self.base_atk, self.atk_per_point);
+        Some(i32::try_from(total).unwrap_or(i32::MAX))
+    }
+
+    pub fn allows(&self, hp: i32, atk: i32) -> bool {
+        self.cost(hp, atk)
+            .is_some_and(|cost| cost != i32::MAX && cost <= self.points)
+    }
+}
+
+// ===== マッチング（/v1/join, /v1/matches） =====
+
+/// ロビーの種類。人数の上限とバトルの人数（NPC で埋める）が違う
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum GameMode {
+    /// 100 人のバトル（mode 導入前と同じ）
+    #[default]
+    Classic,
+    /// 20 人のバトル、プレイヤーは 10 人まで
+    Skirmish,
+    /// 1 対 1（相手が来なければ NPC）
+    Duel,
+    /// このビルドが知らない mode（新しいサーバのロビー・履歴を読むとき用）
+    #[serde(other)]
+    Unknown,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Classic, GameMode::Skirmish, GameMode::Duel];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GameMode::Classic => "classic",
+            GameMode::Skirmish => "skirmish",
+            GameMode::Duel => "duel",
+            GameMode::Unknown => "unknown",
+        }
+    }
+}
+
+/// GET /v1/lobbies（締め切り前のロビー）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct LobbyInfo {
+    /// JoinRequest::lobby に入れるとこのロビーに入る
+    pub id: u64,
+    pub mode: GameMode,
+    /// 参加しているキャラクターの数（squad も1人ずつ数える）
+    pub players: usize,
+    /// この人数に達したら締め切りを待たずに始まる
+    pub capacity: usize,
+    /// 1回の参加で入れるキャラクターの数（squad と本人の合計）
+    pub max_squad: usize,
+    /// NPC を含めたバトルの人数
+    pub battle_size: usize,
+    /// 締め切りまでの秒数（切り上げ）
+    pub seconds_left: u64,
+}
+
+/// 本人と一緒に参加する2人目以降のキャラクター（予算は1人ずつ確認する）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct SquadMember {
+    /// 1-16 文字の英数字と '_' '-'。キャラクター名は "<アカウント名>/<name>" になる
+    pub name: String,
+    pub hp: i32,
+    pub atk: i32,
+    #[serde(default)]
+    pub class: CharacterClass,
+    #[serde(default)]
+    pub targeting: Targeting,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct JoinRequest {
+    /// 省略可。指定する場合はログイン中のアカウント名と一致させる
+    #[serde(default)]
+    pub name: String,
+    pub hp: i32,
+    pub atk: i32,
+    /// 参加取り消し（DELETE /v1/tickets/{id}）に使う ID。
+    /// 結果が返るまでサーバの振った ID は分からないので、取り消すつもりなら必ず指定する
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<String>,
+    /// hp / atk にかける補正（予算の確認は補正前の値で行う）
+    #[serde(default)]
+    pub class: CharacterClass,
+    #[serde(default)]
+    pub targeting: Targeting,
+    /// lobby を省略したとき、この mode の空いているロビーに入る（無ければ作る）
+    #[serde(default)]
+    pub mode: GameMode,
+    /// GET /v1/lobbies の id。締め切り済みなら not_found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lobby: Option<u64>,
+    /// 一緒に参加するキャラクター（本人を含めて LobbyInfo::max_squad 人まで）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub squad: Vec<SquadMember>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct JoinResponse {
+    pub name: String,
+    pub rank: usize,
+    pub final_hp: i32,
+    pub is_winner: bool,
+    /// MatchRecord::id と同じ。GET /v1/matches/{id}/log でバトルの経過を取れる
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_id: Option<u64>,
+    /// JoinRequest::squad と同じ順の結果（上の4項目は本人の結果）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub squad: Vec<ClientCharacterResult>,
+}
+
+/// 結果を受け取らずに抜けたプレイヤー
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct AbandonedPlayer {
+    pub name: String,
+    /// "cancelled": 参加を取り消した / "lobby": バトル開始前に切断 / "result": 結果送信時に切断
+    pub stage: String,
+    /// バトル開始前に切断した場合の扱い（"remove" / "npc"）
+    pub handled_as: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct MatchRecord {
+    pub id: u64,
+    pub finished_at: u64, // UNIX 秒
+    pub players: Vec<String>,
+    pub npc_count: usize,
+    pub winner: Option<String>,
+    #[serde(default)]
+    pub abandoned: Vec<AbandonedPlayer>,
+    #[serde(default)]
+    pub mode: GameMode,
+}
+
+/// GET /v1/matches/{id}/log（サーバが覚えている直近のマッチだけ）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct BattleLog {
+    pub match_id: u64,
+    /// バトルの乱数の seed（engine::battle_rng）。古いサーバは返さない
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// バトル開始時のキャラクター。LogEvent の添字はこの並び
+    pub fighters: Vec<LogFighter>,
+    /// 起きた順
+    pub events: Vec<LogEvent>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct LogFighter {
+    pub name: String,
+    pub hp: i32,
+    pub atk: i32,
+    pub is_client: bool,
+    #[serde(default)]
+    pub targeting: Targeting,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LogEvent {
+    Attack {
+        attacker: usize,
+        defender: usize,
+        damage: i32,
+        /// 攻撃後の残り hp
+        defender_hp: i32,
+    },
+    Death {
+        index: usize,
+    },
+}
+
+// ===== 即時バトル（/v1/battle） =====
+
+/// 両端を含むステータスの範囲（NPC やテスト用キャラの生成に使う）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct StatRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl StatRange {
+    pub const fn new(min: i32, max: i32) -> Self {
+        Self { min, max }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.min <= self.max
+    }
+
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> i32 {
+        rng.gen_range(self.min..=self.max)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ClientCharacterInput {
+    pub name: String,
+    /// 省略時はサーバがランダムに決める
+    #[serde(default)]
+    pub hp: Option<i32>,
+    /// 省略時はサーバがランダムに決める
+    #[serde(default)]
+    pub atk: Option<i32>,
+    #[serde(default)]
+    pub targeting: Targeting,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct BattleRequest {
+    pub characters: Vec<ClientCharacterInput>,
+    /// NPC を含めた人数（省略時は 100）
+    #[serde(default)]
+    pub total_chars: Option<usize>,
+    #[serde(default)]
+    pub npc_hp: Option<StatRange>,
+    #[serde(default)]
+    pub npc_atk: Option<StatRange>,
+    /// 同じシード・同じリクエストなら同じ結果になる
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// true ならクライアント以外も含めた順位表を返す
+    #[serde(default)]
+    pub return_full_standings: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ClientCharacterResult {
+    pub name: String,
+    pub rank: usize,
+    pub final_hp: i32,
+    pub is_winner: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct Standing {
+    pub rank: usize,
+    pub name: String,
+    pub final_hp: i32,
+    pub is_client: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct BattleResult {
+    pub total_chars: usize,
+    pub seed: u64,
+    pub client_results: Vec<ClientCharacterResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standings: Option<Vec<Standing>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ===== CharacterClass::derive_stats =====
+
+    #[test]
+    fn derive_stats_applies_the_class_modifier() {
+        assert_eq!(CharacterClass::Fighter.derive_stats(100, 20), (100, 20));
+        assert_eq!(CharacterClass::Tank.derive_stats(100, 20), (125, 16));
+        assert_eq!(CharacterClass::Striker.derive_stats(100, 20), (80, 25));
+    }
+
+    #[test]
+    fn derive_stats_keeps_hp_positive() {
+        assert_eq!(CharacterClass::Striker.derive_stats(1, 0), (1, 0));
+        assert_eq!(CharacterClass::Fighter.derive_stats(i32::MIN, 0).0, 1);
+    }
+
+    #[test]
+    fn derive_stats_saturates_instead_of_overflowing() {
+        assert_eq!(
+            CharacterClass::Tank.derive_stats(i32::MAX, 0),
+            (i32::MAX, 0)
+        );
+        assert_eq!(
+            CharacterClass::Striker.derive_stats(1, i32::MAX),
+            (1, i32::MAX)
+        );
+        assert_eq!(
+            CharacterClass::Striker.derive_stats(1, i32::MIN).1,
+            i32::MIN
+        );
+    }
+
+    // ===== 知らない enum の値 =====
+
+    #[test]
+    fn unknown_enum_values_decode_to_unknown() {
+        let lobby: LobbyInfo = serde_json::from_value(serde_json::json!({
+            "id": 1, "mode": "battle_royale", "players": 3, "capacity": 64,
+            "battle_size": 64, "seconds_left": 5, "max_squad": 4,
+        }))
+        .unwrap();
+        assert_eq!(lobby.mode, GameMode::Unknown);
+
+        let fighter: LogFighter = serde_json::from_value(serde_json::json!({
+            "name": "alice", "hp": 90, "atk": 20, "is_client": true, "targeting": "sneakiest",
+        }))
+        .unwrap();
+        assert_eq!(fighter.targeting, Targeting::Unknown);
+
+        let class: CharacterClass = serde_json::from_str("\"healer\"").unwrap();
+        assert_eq!(class, CharacterClass::Unknown);
+        assert_eq!(class.derive_stats(100, 20), (100, 20));
+    }
+
+    #[test]
+    fn known_enum_values_keep_their_names() {
+        for mode in GameMode::ALL {
+            let json = serde_json::to_value(mode).unwrap();
+            assert_eq!(json, mode.as_str());
+            assert_eq!(serde_json::from_value::<GameMode>(json).unwrap(), mode);
+        }
+        for class in CharacterClass::ALL {
+            let json = serde_json::to_value(class).unwrap();
+            assert_eq!(json, class.as_str());
+            assert_eq!(
+                serde_json::from_value::<CharacterClass>(json).unwrap(),
+                class
+            );
+        }
+        for targeting in Targeting::ALL {
+            let json = serde_json::to_value(targeting).unwrap();
+            assert_eq!(json, targeting.as_str());
+            assert_eq!(
+                serde_json::from_value::<Targeting>(json).unwrap(),
+                targeting
+            );
+        }
+    }
+
+    // ===== StatBudget =====
+
+    #[test]
+    fn cost_rounds_partial_points_up() {
+        let budget = StatBudget::default();
+        assert_eq!(budget.cost(80, 5), Some(0));
+        assert_eq!(budget.cost(81, 5), Some(1));
+        assert_eq!(budget.cost(82, 5), Some(1));
+        assert_eq!(budget.cost(160, 45), Some(80));
+    }
+
+    #[test]
+    fn cost_below_the_base_is_none() {
+        let budget = StatBudget::default();
+        assert_eq!(budget.cost(79, 5), None);
+        assert_eq!(budget.cost(80, 4), None);
+        assert_eq!(budget.cost(i32::MIN, i32::MIN), None);
+    }
+
+    #[test]
+    fn cost_treats_overflow_as_over_budget() {
+        let budget = StatBudget {
+            points: i32::MAX,
+            base_hp: i32::MIN,
+            base_atk: i32::MIN,
+            hp_per_point: 1,
+            atk_per_point: 1,
+        };
+        assert_eq!(budget.cost(i32::MAX, i32::MAX), Some(i32::MAX));
+        assert!(!budget.allows(i32::MAX, i32::MAX));
+        assert!(!StatBudget::default().allows(i32::MAX, i32::MAX));
+    }
+
+    #[test]
+    fn cost_ignores_non_positive_rates() {
+        let budget = StatBudget {
+            hp_per_point: 0,
+            atk_per_point: -3,
+            ..StatBudget::default()
+        };
+        assert_eq!(budget.cost(90, 10), Some(15));
+    }
+
+    #[test]
+    fn allows_exactly_the_budget() {
+        let budget = StatBudget::default();
+        let (hp, atk) = budget.stats(30, 10);
+        assert_eq!((hp, atk), (140, 15));
+        assert!(budget.allows(hp, atk));
+        assert!(!budget.allows(hp + 2, atk));
+        assert!(!budget.allows(hp, atk + 1));
+    }
+
+    #[test]
+    fn stats_saturate() {
+        let budget = StatBudget {
+            hp_per_point: i32::MAX,
+            ..StatBudget::default()
+        };
+        assert_eq!(budget.stats(2, 0), (i32::MAX, 5));
+    }
+}
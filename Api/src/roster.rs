@@ -0,0 +1,383 @@
+use crate::engine::Fighter;
+use crate::{CharacterClass, Targeting, MAX_STAT};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+// ===== ロスターファイル =====
+//
+// キャラクターの一覧を JSON / TOML で受け渡すための形式。
+// サーバ（/v1/battle/roster, hello_world --roster）が読み、クライアントが書き出す。
+// 通信用の型と違ってファイル形式なので、知らないフィールドはエラーにする。
+//
+//   version = 1
+//
+//   [[characters]]
+//   name = "Alice"
+//   hp = 90
+//   atk = 30
+//   class = "tank"     # 省略時は fighter
+//   targeting = "weakest"  # 省略時は random（version 2 から）
+
+/// このビルドが書き出すロスターのバージョン（これ以下なら読める）
+///
+/// 2: targeting を追加
+pub const ROSTER_VERSION: u32 = 2;
+
+/// 名前の最大長（文字数）
+pub const MAX_NAME_CHARS: usize = 32;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Roster {
+    pub version: u32,
+    pub characters: Vec<RosterCharacter>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RosterCharacter {
+    pub name: String,
+    pub hp: i32,
+    pub atk: i32,
+    #[serde(default)]
+    pub class: CharacterClass,
+    #[serde(default)]
+    pub targeting: Targeting,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RosterFormat {
+    Json,
+    Toml,
+}
+
+impl RosterFormat {
+    /// 拡張子（.json / .toml）から判定する
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(RosterFormat::Json),
+            "toml" => Some(RosterFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Content-Type（application/json / application/toml）から判定する
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "application/json" => Some(RosterFormat::Json),
+            "application/toml" | "text/toml" => Some(RosterFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+// ===== エラー =====
+
+#[derive(Debug)]
+pub struct RosterError {
+    /// 問題のある場所（"characters[2].hp" など）。ファイル全体の問題なら None
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl RosterError {
+    fn at(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: Some(field.into()),
+            message: message.into(),
+        }
+    }
+
+    fn whole(message: impl Into<String>) -> Self {
+        Self {
+            field: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for RosterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}: {}", field, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for RosterError {}
+
+// ===== 読み書き =====
+
+/// バージョンだけ先に読んで、新しすぎるファイルに分かりやすいエラーを出すため
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: Option<u32>,
+}
+
+impl Roster {
+    pub fn new(characters: Vec<RosterCharacter>) -> Self {
+        Self {
+            version: ROSTER_VERSION,
+            characters,
+        }
+    }
+
+    /// パースと検証をまとめて行う
+    pub fn parse(text: &str, format: RosterFormat) -> Result<Self, RosterError> {
+        let probe: Result<VersionProbe, String> = match format {
+            RosterFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            RosterFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+        };
+        match probe {
+            Ok(VersionProbe { version: None }) => {
+                return Err(RosterError::at("version", "missing roster version"))
+            }
+            Ok(VersionProbe {
+                version: Some(v), ..
+            }) if v == 0 || v > ROSTER_VERSION => {
+                return Err(RosterError::at(
+                    "version",
+                    format!(
+                        "unsupported roster version {} (this build reads up to version {})",
+                        v, ROSTER_VERSION
+                    ),
+                ))
+            }
+            // 構文エラーはこの下でまとめて報告する
+            _ => {}
+        }
+
+        let roster: Roster = match format {
+            RosterFormat::Json => serde_json::from_str(text)
+                .map_err(|e| RosterError::whole(format!("invalid JSON roster: {}", e)))?,
+            RosterFormat::Toml => toml::from_str(text)
+                .map_err(|e| RosterError::whole(format!("invalid TOML roster: {}", e)))?,
+        };
+        roster.validate()?;
+        Ok(roster)
+    }
+
+    /// ファイルを読み込む（形式は拡張子で判定）
+    pub fn load(path: &Path) -> Result<Self, RosterError> {
+        let format = RosterFormat::from_path(path).ok_or_else(|| {
+            RosterError::whole(format!(
+                "{}: roster files must end in .json or .toml",
+                path.display()
+            ))
+        })?;
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| RosterError::whole(format!("failed to read {}: {}", path.display(), e)))?;
+        Self::parse(&text, format)
+    }
+
+    pub fn to_string(&self, format: RosterFormat) -> Result<String, RosterError> {
+        match format {
+            RosterFormat::Json => {
+                serde_json::to_string_pretty(self).map_err(|e| RosterError::whole(e.to_string()))
+            }
+            RosterFormat::Toml => {
+                toml::to_string_pretty(self).map_err(|e| RosterError::whole(e.to_string()))
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), RosterError> {
+        if self.characters.is_empty() {
+            return Err(RosterError::at(
+                "characters",
+                "roster must contain at least one character",
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        for (i, c) in self.characters.iter().enumerate() {
+            let field = |name: &str| format!("characters[{}].{}", i, name);
+
+            let name_len = c.name.trim().chars().count();
+            if name_len == 0 || name_len > MAX_NAME_CHARS {
+                return Err(RosterError::at(
+                    field("name"),
+                    format!("name must be 1-{} characters", MAX_NAME_CHARS),
+                ));
+            }
+            if !seen.insert(c.name.as_str()) {
+                return Err(RosterError::at(
+                    field("name"),
+                    format!("duplicate character name '{}'", c.name),
+                ));
+            }
+            if c.hp <= 0 {
+                return Err(RosterError::at(field("hp"), "hp must be positive"));
+            }
+            if c.atk < 0 {
+                return Err(RosterError::at(field("atk"), "atk must not be negative"));
+            }
+            // serde(other) で読めてしまう知らない値はファイルの誤りとして扱う
+            if c.class == CharacterClass::Unknown {
+                return Err(RosterError::at(
+                    field("class"),
+                    format!(
+                        "class must be one of {}",
+                        CharacterClass::ALL.map(CharacterClass::as_str).join(", ")
+                    ),
+                ));
+            }
+            if c.targeting == Targeting::Unknown {
+                return Err(RosterError::at(
+                    field("targeting"),
+                    format!(
+                        "targeting must be one of {}",
+                        Targeting::ALL.map(Targeting::as_str).join(", ")
+                    ),
+                ));
+            }
+            // 上限はクラス補正をかけた後の値で見る（/battle の入力チェックと同じ）
+            let (hp, atk) = c.class.derive_stats(c.hp, c.atk);
+            for (name, value) in [("hp", hp), ("atk", atk)] {
+                if value > MAX_STAT {
+                    return Err(RosterError::at(
+                        field(name),
+                        format!(
+                            "{} must be at most {} after the {} class modifier",
+                            name,
+                            MAX_STAT,
+                            c.class.as_str()
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// クラス補正をかけたうえでバトル用のキャラにする
+    pub fn to_fighters(&self, is_client: bool) -> Vec<Fighter> {
+        self.characters
+            .iter()
+            .map(|c| {
+                let (hp, atk) = c.class.derive_stats(c.hp, c.atk);
+                Fighter {
+                    name: c.name.clone(),
+                    hp,
+                    atk,
+                    is_client,
+                    targeting: c.targeting,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roster(hp: i32, atk: i32, class: CharacterClass) -> Roster {
+        Roster {
+            version: ROSTER_VERSION,
+            characters: vec![RosterCharacter {
+                name: "Alice".to_string(),
+                hp,
+                atk,
+                class,
+                targeting: Targeting::Random,
+            }],
+        }
+    }
+
+    fn error_field(roster: &Roster) -> Option<String> {
+        roster.validate().err().and_then(|e| e.field)
+    }
+
+    #[test]
+    fn targeting_is_read_and_defaults_to_random() {
+        let text = r#"
+            version = 2
+
+            [[characters]]
+            name = "Alice"
+            hp = 90
+            atk = 30
+            targeting = "weakest"
+
+            [[characters]]
+            name = "Bob"
+            hp = 90
+            atk = 30
+        "#;
+        let roster = Roster::parse(text, RosterFormat::Toml).unwrap();
+        let targeting: Vec<Targeting> = roster
+            .to_fighters(false)
+            .iter()
+            .map(|f| f.targeting)
+            .collect();
+        assert_eq!(targeting, [Targeting::Weakest, Targeting::Random]);
+
+        // version 1 のファイルもそのまま読める
+        let v1 = r#"{ "version": 1, "characters": [{ "name": "Alice", "hp": 90, "atk": 30 }] }"#;
+        assert!(Roster::parse(v1, RosterFormat::Json).is_ok());
+    }
+
+    #[test]
+    fn unknown_class_and_targeting_are_rejected() {
+        let mut r = roster(100, 10, CharacterClass::Unknown);
+        assert_eq!(error_field(&r).as_deref(), Some("characters[0].class"));
+
+        r.characters[0].class = CharacterClass::Tank;
+        r.characters[0].targeting = Targeting::Unknown;
+        assert_eq!(error_field(&r).as_deref(), Some("characters[0].targeting"));
+    }
+
+    #[test]
+    fn stats_up_to_the_ceiling_are_valid() {
+        assert!(roster(MAX_STAT, MAX_STAT, CharacterClass::Fighter)
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn stats_above_the_ceiling_are_rejected() {
+        let cases = [
+            (
+                MAX_STAT + 1,
+                10,
+                CharacterClass::Fighter,
+                "characters[0].hp",
+            ),
+            (
+                100,
+                MAX_STAT + 1,
+                CharacterClass::Fighter,
+                "characters[0].atk",
+            ),
+            (i32::MAX, 10, CharacterClass::Fighter, "characters[0].hp"),
+            (100, i32::MAX, CharacterClass::Striker, "characters[0].atk"),
+        ];
+        for (hp, atk, class, field) in cases {
+            assert_eq!(
+                error_field(&roster(hp, atk, class)).as_deref(),
+                Some(field),
+                "hp {} atk {} {:?}",
+                hp,
+                atk,
+                class
+            );
+        }
+    }
+
+    #[test]
+    fn ceiling_applies_after_the_class_modifier() {
+        // tank は hp 125% なので MAX_STAT そのままでは超える
+        assert_eq!(
+            error_field(&roster(MAX_STAT, 10, CharacterClass::Tank)).as_deref(),
+            Some("characters[0].hp")
+        );
+        assert!(roster(MAX_STAT * 4 / 5, 10, CharacterClass::Tank)
+            .validate()
+            .is_ok());
+    }
+}
@@ -0,0 +1,199 @@
+# battle_client_gui の表示（英語）
+# クラス・狙い方・mode の名前は API の値のまま出すので、ここには無い
+
+## 共通
+
+label-language = Language
+label-status = Status: { $status }
+label-name = Name:
+button-check = Check
+button-edit = Edit
+button-save = Save
+button-load = Load
+button-delete = Delete
+button-remove = Remove
+button-clear = Clear
+button-cancel = Cancel
+button-apply = Apply
+button-defaults = Defaults
+button-add = Add
+col-character = Character
+col-rank = Rank
+col-mode = Mode
+font-not-found = No CJK font found; Japanese text may not display (set BATTLE_CJK_FONT to a .ttf / .otf / .ttc file)
+
+## 接続・サーバ
+
+url-empty = Server URL is empty
+url-invalid = Invalid Server URL: { $error }
+parse-msgpack = MessagePack parse error: { $error }
+parse-json = JSON parse error: { $error }
+net-timeout = Request timed out
+net-connect = Could not connect: { $error }
+net-request = Request error: { $error }
+version-not-reported = server does not report its protocol version
+label-server-url = Server URL:
+label-server = Server:
+server-version = v{ $version } (protocol { $protocol }, client { $client })
+server-unknown = (unknown)
+header-servers = Servers
+header-connection-settings = Connection settings
+label-connect-timeout = Connect timeout:
+label-request-timeout = Request timeout:
+label-join-timeout = Join timeout:
+label-max-retries = Max retries:
+status-settings-applied = Applied connection settings
+status-client-too-old = This client is too old for the server (protocol { $client }, server requires { $min } or newer). Please update.
+status-version-check-failed = Version check failed: { $message }
+status-retrying = { $reason } - retrying in { $delay }s ({ $attempt }/{ $max })
+ping-pending = checking...
+ping-ok = { $ms } ms (v{ $version })
+ping-failed = unreachable: { $message }
+profile-account = as { $name }
+button-use = Use
+button-save-current = Save current
+button-check-all = Check all
+servers-none = (no saved servers)
+status-server-used = Using server '{ $name }'
+status-profile-name-empty = Profile name is empty
+status-server-saved = Saved server '{ $name }'
+status-server-deleted = Deleted server '{ $name }'
+status-server-added = Added server '{ $name }'
+button-discover = Discover (LAN)
+button-discovering = Discovering...
+status-no-servers-found = No servers found on the LAN
+status-servers-found = Found { $count ->
+    [one] 1 server
+   *[other] { $count } servers
+}
+status-discovery-failed = Discovery failed: { $message }
+
+## ログイン
+
+tab-lobby = Lobby
+tab-character = Character
+tab-history = History
+tab-arena = Arena
+label-password = Password:
+button-login = Login
+button-register = Register
+button-stop = Stop
+button-logout = Logout
+label-logged-in-as = Logged in as: { $name }
+status-idle = Idle
+status-logged-in = Logged in as { $name }
+status-logged-out = Logged out
+status-not-logged-in = Not logged in
+status-name-empty = Name is empty
+status-waiting-auth = Waiting... (POST /v1/{ $endpoint })
+status-cancelled = Cancelled
+status-error = Error: { $message }
+status-session-expired = Please log in again: { $message }
+
+## ロビー・参加
+
+label-character = Character:
+label-roster-file = Roster file:
+button-export = Export
+roster-bad-extension = roster file must end in .json or .toml
+status-roster-exported = Exported roster to { $path }
+status-export-failed = Export failed: { $message }
+header-lobbies = Lobbies
+button-quick-join = Quick join
+hover-quick-join = Join the classic lobby, as before
+label-result = Result:
+result-name = name      : { $name }
+result-rank = rank      : { $rank }
+result-final-hp = final_hp  : { $final_hp }
+result-is-winner = is_winner : { $is_winner }
+result-none = (no result)
+button-watch-replay = Watch replay
+note-join-wait = Note: /join waits until the response arrives (10 s wait + battle time).
+status-waiting-lobby = Waiting... (POST /v1/join, lobby #{ $lobby })
+status-waiting-mode = Waiting... (POST /v1/join, { $mode })
+status-matching = Waiting... (server is matching / battling)
+status-done = Done
+status-cancelling = Cancelling... (DELETE /v1/tickets)
+status-cancel-failed = Cancel failed: { $message }
+status-log-unavailable = Battle log unavailable: { $message }
+status-lobbies-failed = Lobby list failed: { $message }
+lobbies-single = (this server has a single lobby - use Quick join)
+label-mode = Mode:
+button-join-mode = Join mode
+hover-join-mode = Join an open lobby of this mode, or open a new one
+button-refresh = Refresh
+button-join = Join
+lobbies-none = (no open lobbies)
+col-players = Players
+col-starts-in = Starts in
+hover-battle-size = { $count } fighters incl. NPCs
+hover-max-squad = up to { $count } characters per join
+
+## キャラクター・スクワッド
+
+stat-points = Stat points: { $used } / { $points } (base HP { $base_hp }, base ATK { $base_atk })
+slider-hp = HP (+{ $per_point } / pt)
+slider-atk = ATK (+{ $per_point } / pt)
+label-class = Class
+label-targeting = Targeting
+label-preview = Preview:
+label-preset-name = Preset name:
+button-add-to-squad = + Squad
+presets-none = (no presets)
+status-preset-name-empty = Preset name is empty
+status-preset-saved = Saved preset '{ $name }'
+status-preset-loaded = Loaded preset '{ $name }'
+status-preset-deleted = Deleted preset '{ $name }'
+squad-heading = Squad (you + { $count }):
+squad-unsupported = (this server does not take squads - only your own character joins)
+label-squad = Squad:
+button-add-current = Add current
+hover-add-current = Add the character above to the squad, named after the preset name
+status-squad-invalid-name = Squad names must be 1-16 letters, digits, '_' or '-'
+status-squad-duplicate = '{ $name }' is already in the squad
+status-squad-added = Added '{ $name }' to the squad
+status-squad-removed = Removed '{ $name }' from the squad
+col-final-hp = Final HP
+result-winner = WINNER
+
+## 履歴
+
+history-none = (no matches yet)
+history-summary = Matches: { $matches }  Wins: { $wins }  Best: { $best }  Avg rank: { $avg }
+status-history-cleared = Cleared history
+history-rank-chart = Rank (top = 1st):
+history-hp-chart = Final HP:
+col-date = Date (UTC)
+col-hp = HP
+col-server = Server
+history-rank-win = { $rank } (win)
+
+## アリーナ
+
+arena-match = Match #{ $id }
+arena-loading = Loading battle log...
+arena-none = (no battle log yet - join a match to watch it here)
+arena-empty = (empty battle)
+hover-restart = Restart
+button-play = Play
+button-pause = Pause
+slider-speed = Speed
+slider-event = Event
+arena-alive = Alive: { $alive } / { $total }
+arena-winner = Winner: { $name }
+arena-no-winner = No winner
+arena-fighter = { $name }
+    HP { $hp } / { $max_hp }  ATK { $atk }
+arena-fighter-down = { $name }
+    HP { $hp } / { $max_hp }  ATK { $atk } (down)
+
+## 結果の検証
+
+verify-ok = ✔ Verified: the battle log has the stats you sent and a local re-run gives the same rank and final HP
+verify-mismatch = ⚠ The battle log or a local re-run of the battle does not match the server's result
+verify-mismatch-line = { $name }: server rank { $rank } / HP { $final_hp }, local rank { $local_rank } / HP { $local_final_hp }
+verify-missing-line = { $name }: not in the battle log roster
+verify-stats-line = { $name }: sent HP { $hp } / ATK { $atk } / { $targeting }, but the log has HP { $logged_hp } / ATK { $logged_atk } / { $logged_targeting }
+verify-unverifiable = (this result cannot be verified - the battle log is unavailable or has no seed)
+verify-pending = Verifying the result...
+status-verify-mismatch = Result of match #{ $id } does not match a local re-run
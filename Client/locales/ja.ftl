@@ -0,0 +1,195 @@
+# battle_client_gui の表示（日本語）
+
+## 共通
+
+label-language = 言語
+label-status = 状態: { $status }
+label-name = 名前:
+button-check = 確認
+button-edit = 編集
+button-save = 保存
+button-load = 読込
+button-delete = 削除
+button-remove = 外す
+button-clear = クリア
+button-cancel = キャンセル
+button-apply = 適用
+button-defaults = 初期値
+button-add = 追加
+col-character = キャラクター
+col-rank = 順位
+col-mode = モード
+font-not-found = CJK フォントが見つかりません。日本語が表示されない場合は BATTLE_CJK_FONT に .ttf / .otf / .ttc を指定してください
+
+## 接続・サーバ
+
+url-empty = サーバ URL が空です
+url-invalid = サーバ URL が不正です: { $error }
+parse-msgpack = MessagePack を読めません: { $error }
+parse-json = JSON を読めません: { $error }
+net-timeout = 応答がありません（タイムアウト）
+net-connect = 接続できません: { $error }
+net-request = リクエストのエラー: { $error }
+version-not-reported = サーバがプロトコルのバージョンを返しません
+label-server-url = サーバ URL:
+label-server = サーバ:
+server-version = v{ $version }（プロトコル { $protocol }、クライアント { $client }）
+server-unknown = （不明）
+header-servers = サーバ
+header-connection-settings = 接続設定
+label-connect-timeout = 接続タイムアウト:
+label-request-timeout = リクエストタイムアウト:
+label-join-timeout = 参加タイムアウト:
+label-max-retries = 再試行回数:
+status-settings-applied = 接続設定を適用しました
+status-client-too-old = このクライアントはサーバに対して古すぎます（プロトコル { $client }、サーバは { $min } 以上が必要）。更新してください。
+status-version-check-failed = バージョンを確認できません: { $message }
+status-retrying = { $reason } - { $delay } 秒後に再試行します（{ $attempt }/{ $max }）
+ping-pending = 確認中...
+ping-ok = { $ms } ms（v{ $version }）
+ping-failed = 繋がりません: { $message }
+profile-account = { $name } でログイン中
+button-use = 使う
+button-save-current = 今の接続先を保存
+button-check-all = すべて確認
+servers-none = （保存したサーバはありません）
+status-server-used = サーバ '{ $name }' を使います
+status-profile-name-empty = プロファイル名が空です
+status-server-saved = サーバ '{ $name }' を保存しました
+status-server-deleted = サーバ '{ $name }' を削除しました
+status-server-added = サーバ '{ $name }' を追加しました
+button-discover = LAN から探す
+button-discovering = 探しています...
+status-no-servers-found = LAN にサーバが見つかりません
+status-servers-found = サーバが { $count } 台見つかりました
+status-discovery-failed = 探せませんでした: { $message }
+
+## ログイン
+
+tab-lobby = ロビー
+tab-character = キャラクター
+tab-history = 履歴
+tab-arena = アリーナ
+label-password = パスワード:
+button-login = ログイン
+button-register = 登録
+button-stop = 中止
+button-logout = ログアウト
+label-logged-in-as = ログイン中: { $name }
+status-idle = 待機中
+status-logged-in = { $name } でログインしました
+status-logged-out = ログアウトしました
+status-not-logged-in = ログインしていません
+status-name-empty = 名前が空です
+status-waiting-auth = 待機中...（POST /v1/{ $endpoint }）
+status-cancelled = キャンセルしました
+status-error = エラー: { $message }
+status-session-expired = ログインし直してください: { $message }
+
+## ロビー・参加
+
+label-character = キャラクター:
+label-roster-file = ロスターファイル:
+button-export = 書き出し
+roster-bad-extension = ロスターファイルの拡張子は .json か .toml にしてください
+status-roster-exported = ロスターを { $path } に書き出しました
+status-export-failed = 書き出せませんでした: { $message }
+header-lobbies = ロビー一覧
+button-quick-join = クイック参加
+hover-quick-join = これまでどおり classic のロビーに入ります
+label-result = 結果:
+result-name = 名前     : { $name }
+result-rank = 順位     : { $rank }
+result-final-hp = 最終HP   : { $final_hp }
+result-is-winner = 勝者     : { $is_winner }
+result-none = （結果なし）
+button-watch-replay = リプレイを見る
+note-join-wait = 注意: /join はレスポンスが返るまで待機します（10秒待機 + バトル時間）。
+status-waiting-lobby = 待機中...（POST /v1/join、ロビー #{ $lobby }）
+status-waiting-mode = 待機中...（POST /v1/join、{ $mode }）
+status-matching = 待機中...（サーバでマッチング / バトル中）
+status-done = 完了
+status-cancelling = 取り消し中...（DELETE /v1/tickets）
+status-cancel-failed = 取り消せませんでした: { $message }
+status-log-unavailable = バトルログを取得できません: { $message }
+status-lobbies-failed = ロビー一覧を取得できません: { $message }
+lobbies-single = （このサーバのロビーは1つだけです。クイック参加を使ってください）
+label-mode = モード:
+button-join-mode = このモードで参加
+hover-join-mode = このモードの空いているロビーに入ります（無ければ新しく開きます）
+button-refresh = 更新
+button-join = 参加
+lobbies-none = （開いているロビーはありません）
+col-players = 人数
+col-starts-in = 開始まで
+hover-battle-size = NPC を含めて { $count } 人で戦います
+hover-max-squad = 1回の参加で最大 { $count } 人
+
+## キャラクター・スクワッド
+
+stat-points = ステータスポイント: { $used } / { $points }（基本 HP { $base_hp }、基本 ATK { $base_atk }）
+slider-hp = HP（1pt あたり +{ $per_point }）
+slider-atk = ATK（1pt あたり +{ $per_point }）
+label-class = クラス
+label-targeting = 狙い方
+label-preview = プレビュー:
+label-preset-name = プリセット名:
+button-add-to-squad = + スクワッド
+presets-none = （プリセットはありません）
+status-preset-name-empty = プリセット名が空です
+status-preset-saved = プリセット '{ $name }' を保存しました
+status-preset-loaded = プリセット '{ $name }' を読み込みました
+status-preset-deleted = プリセット '{ $name }' を削除しました
+squad-heading = スクワッド（本人 + { $count } 人）:
+squad-unsupported = （このサーバはスクワッドに対応していません。本人だけが参加します）
+label-squad = スクワッド:
+button-add-current = 今のキャラを追加
+hover-add-current = 上のキャラクターをプリセット名でスクワッドに加えます
+status-squad-invalid-name = スクワッドの名前は 1-16 文字の英数字、'_'、'-' にしてください
+status-squad-duplicate = '{ $name }' はもうスクワッドにいます
+status-squad-added = '{ $name }' をスクワッドに加えました
+status-squad-removed = '{ $name }' をスクワッドから外しました
+col-final-hp = 最終HP
+result-winner = 勝者
+
+## 履歴
+
+history-none = （まだマッチの記録はありません）
+history-summary = マッチ数: { $matches }  勝利: { $wins }  最高: { $best } 位  平均順位: { $avg }
+status-history-cleared = 履歴を消去しました
+history-rank-chart = 順位（上ほど上位）:
+history-hp-chart = 最終HP:
+col-date = 日時（UTC）
+col-hp = HP
+col-server = サーバ
+history-rank-win = { $rank }（勝利）
+
+## アリーナ
+
+arena-match = マッチ #{ $id }
+arena-loading = バトルログを読み込んでいます...
+arena-none = （バトルログはまだありません。マッチに参加するとここで見られます）
+arena-empty = （参加者がいません）
+hover-restart = 最初から
+button-play = 再生
+button-pause = 一時停止
+slider-speed = 速さ
+slider-event = イベント
+arena-alive = 生存: { $alive } / { $total }
+arena-winner = 勝者: { $name }
+arena-no-winner = 勝者なし
+arena-fighter = { $name }
+    HP { $hp } / { $max_hp }  ATK { $atk }
+arena-fighter-down = { $name }
+    HP { $hp } / { $max_hp }  ATK { $atk }（戦闘不能）
+
+## 結果の検証
+
+verify-ok = ✔ 検証済み: バトルログのステータスは送ったとおりで、手元でやり直しても順位と最終HPが同じでした
+verify-mismatch = ⚠ バトルログか手元でやり直したバトルがサーバの結果と一致しません
+verify-mismatch-line = { $name }: サーバ { $rank } 位 / HP { $final_hp }、手元 { $local_rank } 位 / HP { $local_final_hp }
+verify-missing-line = { $name }: バトルログのロスターにいません
+verify-stats-line = { $name }: 送ったのは HP { $hp } / ATK { $atk } / { $targeting } ですが、ログでは HP { $logged_hp } / ATK { $logged_atk } / { $logged_targeting } です
+verify-unverifiable = （この結果は検証できません。バトルログが取れないか、seed がありません）
+verify-pending = 結果を検証しています...
+status-verify-mismatch = マッチ #{ $id } の結果が手元の再計算と一致しません
@@ -0,0 +1,337 @@
+use crate::AppState;
+use battle_api::{t, BattleLog, LogEvent};
+use eframe::egui;
+
+// ===== アリーナ（バトルログの再生） =====
+//
+// GET /v1/matches/{id}/log のイベントを1件ずつ適用して、全員のマスを格子状に描く。
+// 再生位置はイベントの番号で、巻き戻すときは最初から適用し直す。
+
+/// 攻撃の線を何件前まで残すか
+const ATTACK_TRAIL: usize = 12;
+/// 倒れたキャラが薄くなりきるまでのイベント数
+const FADE_EVENTS: f32 = 40.0;
+/// 倒れたキャラの最終的な濃さ
+const DEAD_ALPHA: f32 = 0.15;
+
+pub struct Arena {
+    log: BattleLog,
+    own: Vec<usize>, // 自分のキャラクター（squad を含む）の添字
+    playing: bool,
+    speed: f32,    // 1秒あたりのイベント数
+    position: f32, // 再生位置（イベントの番号、小数は次のイベントまでの途中）
+
+    // events[..applied] を適用した状態
+    applied: usize,
+    hp: Vec<i32>,
+    died_at: Vec<Option<usize>>, // Death イベントの番号
+}
+
+impl Arena {
+    /// own_names は自分のキャラクター名（is_client のキャラから探す）
+    pub fn new(log: BattleLog, own_names: &[String]) -> Self {
+        let own = log
+            .fighters
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.is_client && own_names.contains(&f.name))
+            .map(|(i, _)| i)
+            .collect();
+        let hp = log.fighters.iter().map(|f| f.hp).collect();
+        let died_at = vec![None; log.fighters.len()];
+        Self {
+            log,
+            own,
+            playing: true,
+            speed: 60.0,
+            position: 0.0,
+            applied: 0,
+            hp,
+            died_at,
+        }
+    }
+
+    pub fn match_id(&self) -> u64 {
+        self.log.match_id
+    }
+
+    fn finished(&self) -> bool {
+        self.applied >= self.log.events.len()
+    }
+
+    fn seek(&mut self, target: usize) {
+        let target = target.min(self.log.events.len());
+        if target < self.applied {
+            for (hp, f) in self.hp.iter_mut().zip(&self.log.fighters) {
+                *hp = f.hp;
+            }
+            self.died_at.fill(None);
+            self.applied = 0;
+        }
+        while self.applied < target {
+            match self.log.events[self.applied] {
+                LogEvent::Attack {
+                    defender,
+                    defender_hp,
+                    ..
+                } => {
+                    if let Some(hp) = self.hp.get_mut(defender) {
+                        *hp = defender_hp;
+                    }
+                }
+                LogEvent::Death { index } => {
+                    if let Some(died) = self.died_at.get_mut(index) {
+                        *died = Some(self.applied);
+                    }
+                }
+            }
+            self.applied += 1;
+        }
+    }
+
+    fn alive(&self) -> usize {
+        self.died_at.iter().filter(|d| d.is_none()).count()
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let total = self.log.events.len();
+
+        if self.playing {
+            let dt = ui.input(|i| i.stable_dt).min(0.1);
+            self.position = (self.position + dt * self.speed).min(total as f32);
+            self.seek(self.position as usize);
+            if self.finished() {
+                self.playing = false;
+            }
+            ui.ctx().request_repaint();
+        }
+
+        self.ui_controls(ui, total);
+        ui.add_space(4.0);
+        self.paint(ui);
+    }
+
+    fn ui_controls(&mut self, ui: &mut egui::Ui, total: usize) {
+        ui.horizontal(|ui| {
+            if ui.button("⏮").on_hover_text(t!("hover-restart")).clicked() {
+                self.seek(0);
+                self.position = 0.0;
+            }
+            let label = if self.playing {
+                t!("button-pause")
+            } else {
+                t!("button-play")
+            };
+            if ui.button(label).clicked() {
+                if !self.playing && self.finished() {
+                    // 最後まで再生していたら頭から
+                    self.seek(0);
+                    self.position = 0.0;
+                }
+                self.playing = !self.playing;
+            }
+            ui.add(
+                egui::Slider::new(&mut self.speed, 5.0..=500.0)
+                    .logarithmic(true)
+                    .suffix(" ev/s")
+                    .text(t!("slider-speed")),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            let mut target = self.applied;
+            let seek = ui.add(egui::Slider::new(&mut target, 0..=total).text(t!("slider-event")));
+            if seek.changed() {
+                self.seek(target);
+                self.position = target as f32;
+            }
+            ui.label(t!(
+                "arena-alive",
+                alive = self.alive(),
+                total = self.hp.len()
+            ));
+        });
+
+        if self.finished() {
+            let winner = self
+                .died_at
+                .iter()
+                .position(|d| d.is_none())
+                .map(|i| self.log.fighters[i].name.as_str());
+            match winner {
+                Some(name) => ui.label(t!("arena-winner", name = name)),
+                None => ui.label(t!("arena-no-winner")),
+            };
+        }
+    }
+
+    fn paint(&self, ui: &mut egui::Ui) {
+        let n = self.log.fighters.len();
+        if n == 0 {
+            ui.label(t!("arena-empty"));
+            return;
+        }
+        let cols = (n as f32).sqrt().ceil() as usize;
+        let rows = n.div_ceil(cols);
+        let width = ui.available_width().max(200.0);
+        let cell = egui::vec2(width / cols as f32, (width / cols as f32 * 0.6).min(40.0));
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(width, cell.y * rows as f32),
+            egui::Sense::hover(),
+        );
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+        let cell_rect = |i: usize| {
+            let min = rect.min + egui::vec2((i % cols) as f32 * cell.x, (i / cols) as f32 * cell.y);
+            egui::Rect::from_min_size(min, cell).shrink(2.0)
+        };
+
+        let own_color = egui::Color32::GOLD;
+        let client_color = egui::Color32::LIGHT_BLUE;
+        let text_color = visuals.text_color();
+        let font = egui::FontId::proportional(9.0);
+
+        for (i, f) in self.log.fighters.iter().enumerate() {
+            let r = cell_rect(i);
+            let alpha = match self.died_at[i] {
+                None => 1.0,
+                Some(died) => {
+                    let age = (self.position - died as f32).max(0.0);
+                    (1.0 - age / FADE_EVENTS).max(DEAD_ALPHA)
+                }
+            };
+
+            painter.rect_filled(r, 3.0, visuals.faint_bg_color.gamma_multiply(alpha));
+            // 自分のマスは倒れた後も見失わないように枠を残す
+            let outline = if self.own.contains(&i) {
+                egui::Stroke::new(2.0, own_color.gamma_multiply(alpha.max(0.6)))
+            } else if f.is_client {
+                egui::Stroke::new(1.0, client_color.gamma_multiply(alpha))
+            } else {
+                egui::Stroke::new(
+                    1.0,
+                    visuals
+                        .widgets
+                        .noninteractive
+                        .bg_stroke
+                        .color
+                        .gamma_multiply(alpha),
+                )
+            };
+            painter.rect_stroke(r, 3.0, outline);
+
+            painter.text(
+                r.left_top() + egui::vec2(2.0, 1.0),
+                egui::Align2::LEFT_TOP,
+                short_name(&f.name),
+                font.clone(),
+                text_color.gamma_multiply(alpha),
+            );
+
+            // hp バー（開始時の hp を満タンとする）
+            let frac = (self.hp[i].max(0) as f32 / f.hp.max(1) as f32).clamp(0.0, 1.0);
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(r.left() + 2.0, r.bottom() - 6.0),
+                egui::pos2(r.right() - 2.0, r.bottom() - 2.0),
+            );
+            painter.rect_filled(bar, 1.0, visuals.extreme_bg_color.gamma_multiply(alpha));
+            let filled =
+                egui::Rect::from_min_size(bar.min, egui::vec2(bar.width() * frac, bar.height()));
+            painter.rect_filled(filled, 1.0, hp_color(frac).gamma_multiply(alpha));
+        }
+
+        // 直近の攻撃（新しいものほど濃く）
+        let start = self.applied.saturating_sub(ATTACK_TRAIL);
+        for (k, event) in self.log.events[start..self.applied].iter().enumerate() {
+            let LogEvent::Attack {
+                attacker, defender, ..
+            } = *event
+            else {
+                continue;
+            };
+            if attacker >= n || defender >= n {
+                continue;
+            }
+            let recency = (k + 1) as f32 / (self.applied - start) as f32;
+            let involves_own = self.own.contains(&attacker) || self.own.contains(&defender);
+            let color = if involves_own {
+                own_color
+            } else {
+                egui::Color32::from_rgb(230, 80, 60)
+            };
+            let from = cell_rect(attacker).center();
+            let to = cell_rect(defender).center();
+            painter.line_segment(
+                [from, to],
+                egui::Stroke::new(1.5, color.gamma_multiply(recency)),
+            );
+            painter.circle_filled(to, 2.5, color.gamma_multiply(recency));
+        }
+
+        // マスにカーソルを乗せると詳細
+        let hovered = response.hover_pos().and_then(|pos| {
+            let col = ((pos.x - rect.left()) / cell.x) as usize;
+            let row = ((pos.y - rect.top()) / cell.y) as usize;
+            let i = row * cols + col;
+            (col < cols && i < n).then_some(i)
+        });
+        if let Some(i) = hovered {
+            let f = &self.log.fighters[i];
+            let hp = self.hp[i].max(0);
+            let id = if self.died_at[i].is_some() {
+                "arena-fighter-down"
+            } else {
+                "arena-fighter"
+            };
+            response.on_hover_text_at_pointer(t!(
+                id,
+                name = f.name.as_str(),
+                hp = hp,
+                max_hp = f.hp,
+                atk = f.atk
+            ));
+        }
+    }
+}
+
+/// マスに収まるように名前を縮める
+fn short_name(name: &str) -> String {
+    const MAX: usize = 8;
+    if name.chars().count() <= MAX {
+        name.to_string()
+    } else {
+        let mut s: String = name.chars().take(MAX - 1).collect();
+        s.push('…');
+        s
+    }
+}
+
+/// 残り hp の割合で緑 → 黄 → 赤
+fn hp_color(frac: f32) -> egui::Color32 {
+    if frac > 0.5 {
+        let t = (frac - 0.5) * 2.0;
+        egui::Color32::from_rgb((230.0 * (1.0 - t)) as u8 + 40, 200, 60)
+    } else {
+        let t = frac * 2.0;
+        egui::Color32::from_rgb(230, (200.0 * t) as u8, 50)
+    }
+}
+
+impl AppState {
+    pub(crate) fn ui_arena(&mut self, ui: &mut egui::Ui) {
+        match &mut self.arena {
+            Some(arena) => {
+                ui.label(t!("arena-match", id = arena.match_id()));
+                arena.ui(ui);
+            }
+            None if self.log_fetch.is_some() => {
+                ui.label(t!("arena-loading"));
+            }
+            None => {
+                ui.label(t!("arena-none"));
+            }
+        }
+    }
+}
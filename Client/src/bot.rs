@@ -0,0 +1,664 @@
+// 負荷試験用のヘッドレスクライアント
+//
+//   cargo run --release --bin battle_bot -- --server http://127.0.0.1:3000 --players 200 --rate 20
+//
+// プレイヤーごとにアカウント（<prefix>_0001 など）を登録 / ログインし、
+// --rate で決めた間隔で順に /v1/join を送る。--rounds 回参加したら終わる。
+// 最後にレイテンシ（p50 / p90 / p99）と結果の集計を出す。
+// 全員が同じ IP から来るので、サーバの IP ごとの回数制限は外して起動しておく
+// （BATTLE_AUTH_RATE_PER_IP=0 BATTLE_JOIN_RATE_PER_IP=0）。
+// --verify を付けると結果ごとにバトルログを取り、ログのステータスが送ったとおりか、
+// 手元で再計算した順位と最終 hp が同じかを確かめる。
+
+use battle_api::engine::{self, Mismatch, Verification};
+use battle_api::{
+    capability, BattleLog, CharacterClass, CredentialsRequest, ErrorBody, GameMode, JoinRequest,
+    JoinResponse, ServerInfo, StatBudget, Targeting, TokenResponse, API_PREFIX, PROTOCOL_HEADER,
+    PROTOCOL_VERSION,
+};
+use clap::{Parser, ValueEnum};
+use rand::Rng;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// ===== コマンドライン =====
+
+#[derive(Parser)]
+#[command(about = "Spawn simulated players that join a battle server concurrently")]
+struct Args {
+    #[arg(long, default_value = "http://127.0.0.1:3000")]
+    server: String,
+
+    /// 同時に動かすプレイヤー数
+    #[arg(long, default_value_t = 10)]
+    players: usize,
+
+    /// 1秒あたりに参加させるプレイヤー数（0 なら全員同時）
+    #[arg(long, default_value_t = 5.0)]
+    rate: f64,
+
+    /// プレイヤーごとの参加回数（結果が返ったらすぐ次に参加する）
+    #[arg(long, default_value_t = 1)]
+    rounds: u32,
+
+    /// アカウント名の先頭（<prefix>_0001 ...）
+    #[arg(long, default_value = "bot")]
+    name_prefix: String,
+
+    /// 全アカウント共通のパスワード（無ければ登録する）
+    #[arg(long, default_value = "bot-password")]
+    password: String,
+
+    /// クラス補正前の hp（省略時は予算の範囲でランダムに振る）
+    #[arg(long)]
+    hp: Option<i32>,
+
+    #[arg(long)]
+    atk: Option<i32>,
+
+    #[arg(long, value_parser = parse_class, default_value = "fighter")]
+    class: CharacterClass,
+
+    #[arg(long, value_parser = parse_targeting, default_value = "random")]
+    targeting: Targeting,
+
+    /// 入るロビーの mode（古いサーバは無視して classic になる）
+    #[arg(long, value_parser = parse_mode, default_value = "classic")]
+    mode: GameMode,
+
+    /// /v1/register, /v1/login の待ち時間の上限（パスワードのハッシュは重いので長めにする）
+    #[arg(long, default_value_t = 30)]
+    request_timeout_secs: u64,
+
+    /// /v1/join の待ち時間の上限（ロビーの待機 + バトル時間）
+    #[arg(long, default_value_t = 120)]
+    join_timeout_secs: u64,
+
+    /// 429（回数制限・ロビー満員）を何回まで待って送り直すか
+    #[arg(long, default_value_t = 5)]
+    max_retries: u32,
+
+    /// 結果を GET /v1/matches/{id}/log の seed とロスターで再計算して確かめる
+    #[arg(long)]
+    verify: bool,
+
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+fn parse_class(s: &str) -> Result<CharacterClass, String> {
+    CharacterClass::ALL
+        .into_iter()
+        .find(|c| c.as_str() == s)
+        .ok_or_else(|| format!("unknown class '{}'", s))
+}
+
+fn parse_targeting(s: &str) -> Result<Targeting, String> {
+    Targeting::ALL
+        .into_iter()
+        .find(|t| t.as_str() == s)
+        .ok_or_else(|| format!("unknown targeting '{}'", s))
+}
+
+fn parse_mode(s: &str) -> Result<GameMode, String> {
+    GameMode::ALL
+        .into_iter()
+        .find(|m| m.as_str() == s)
+        .ok_or_else(|| format!("unknown mode '{}'", s))
+}
+
+// ===== 計測結果 =====
+
+/// 1回の /v1/join の結果
+enum Outcome {
+    Finished(JoinResponse),
+    /// 成功以外のステータス（ErrorBody.code 付き）
+    Rejected(String),
+    /// 接続できない・タイムアウトなど
+    Network(String),
+}
+
+struct Sample {
+    latency: Duration,
+    throttled: u32, // 送り直した 429 の回数
+    outcome: Outcome,
+    verification: Option<Verification>, // --verify のときだけ
+}
+
+#[derive(Default)]
+struct PlayerReport {
+    login_latency: Option<Duration>,
+    login_error: Option<String>,
+    samples: Vec<Sample>,
+}
+
+#[derive(Serialize)]
+struct Latency {
+    count: usize,
+    p50_ms: f64,
+    p90_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+}
+
+impl Latency {
+    fn from_samples(mut values: Vec<Duration>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort();
+        let pick = |q: f64| {
+            let i = ((values.len() - 1) as f64 * q).round() as usize;
+            values[i].as_secs_f64() * 1000.0
+        };
+        Some(Self {
+            count: values.len(),
+            p50_ms: pick(0.5),
+            p90_ms: pick(0.9),
+            p99_ms: pick(0.99),
+            max_ms: pick(1.0),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct Summary {
+    players: usize,
+    elapsed_secs: f64,
+    login_failures: usize,
+    joins: usize,
+    finished: usize,
+    /// ErrorBody.code（または HTTP ステータス）ごとの件数
+    rejected: BTreeMap<String, usize>,
+    network_errors: usize,
+    throttled_retries: u32,
+    /// 1秒あたりに返った結果の数
+    throughput: f64,
+    login_latency: Option<Latency>,
+    join_latency: Option<Latency>,
+    wins: usize,
+    avg_rank: Option<f64>,
+    best_rank: Option<usize>,
+    avg_final_hp: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verification: Option<VerifySummary>,
+}
+
+#[derive(Serialize)]
+struct VerifySummary {
+    verified: usize,
+    mismatched: usize,
+    unverifiable: usize,
+}
+
+fn summarize(reports: &[PlayerReport], elapsed: Duration) -> Summary {
+    let samples: Vec<&Sample> = reports.iter().flat_map(|r| &r.samples).collect();
+    let results: Vec<&JoinResponse> = samples
+        .iter()
+        .filter_map(|s| match &s.outcome {
+            Outcome::Finished(res) => Some(res),
+            _ => None,
+        })
+        .collect();
+
+    let mut rejected = BTreeMap::new();
+    for s in &samples {
+        if let Outcome::Rejected(code) = &s.outcome {
+            *rejected.entry(code.clone()).or_default() += 1;
+        }
+    }
+
+    let average = |sum: f64| (!results.is_empty()).then(|| sum / results.len() as f64);
+    Summary {
+        players: reports.len(),
+        elapsed_secs: elapsed.as_secs_f64(),
+        login_failures: reports.iter().filter(|r| r.login_error.is_some()).count(),
+        joins: samples.len(),
+        finished: results.len(),
+        rejected,
+        network_errors: samples
+            .iter()
+            .filter(|s| matches!(s.outcome, Outcome::Network(_)))
+            .count(),
+        throttled_retries: samples.iter().map(|s| s.throttled).sum(),
+        throughput: results.len() as f64 / elapsed.as_secs_f64().max(0.001),
+        login_latency: Latency::from_samples(
+            reports.iter().filter_map(|r| r.login_latency).collect(),
+        ),
+        // 結果が返ったものだけ（エラーは即座に返るので混ぜない）
+        join_latency: Latency::from_samples(
+            samples
+                .iter()
+                .filter(|s| matches!(s.outcome, Outcome::Finished(_)))
+                .map(|s| s.latency)
+                .collect(),
+        ),
+        wins: results.iter().filter(|r| r.is_winner).count(),
+        avg_rank: average(results.iter().map(|r| r.rank as f64).sum()),
+        best_rank: results.iter().map(|r| r.rank).min(),
+        avg_final_hp: average(results.iter().map(|r| r.final_hp as f64).sum()),
+        verification: verify_summary(&samples),
+    }
+}
+
+fn verify_summary(samples: &[&Sample]) -> Option<VerifySummary> {
+    let verifications: Vec<&Verification> = samples
+        .iter()
+        .filter_map(|s| s.verification.as_ref())
+        .collect();
+    if verifications.is_empty() {
+        return None;
+    }
+    let count = |f: fn(&Verification) -> bool| verifications.iter().filter(|v| f(v)).count();
+    Some(VerifySummary {
+        verified: count(|v| matches!(v, Verification::Verified)),
+        mismatched: count(|v| matches!(v, Verification::Mismatch(_))),
+        unverifiable: count(|v| matches!(v, Verification::Unverifiable)),
+    })
+}
+
+fn print_text(summary: &Summary, reports: &[PlayerReport]) {
+    println!(
+        "players: {}  elapsed: {:.1}s  throughput: {:.2} results/s",
+        summary.players, summary.elapsed_secs, summary.throughput
+    );
+    println!(
+        "joins: {}  finished: {}  network errors: {}  429 retries: {}",
+        summary.joins, summary.finished, summary.network_errors, summary.throttled_retries
+    );
+    if summary.login_failures > 0 {
+        println!("login failures: {}", summary.login_failures);
+        // 同じ原因が並ぶので最初の1件だけ
+        if let Some(e) = reports.iter().find_map(|r| r.login_error.as_ref()) {
+            println!("  first error: {}", e);
+        }
+    }
+    for (code, count) in &summary.rejected {
+        println!("rejected [{}]: {}", code, count);
+    }
+    if let Some(e) = reports
+        .iter()
+        .flat_map(|r| &r.samples)
+        .find_map(|s| match &s.outcome {
+            Outcome::Network(e) => Some(e),
+            _ => None,
+        })
+    {
+        println!("first network error: {}", e);
+    }
+    for (label, latency) in [
+        ("login", &summary.login_latency),
+        ("join", &summary.join_latency),
+    ] {
+        if let Some(l) = latency {
+            println!(
+                "{:<5} latency (n={}): p50 {:.0}ms  p90 {:.0}ms  p99 {:.0}ms  max {:.0}ms",
+                label, l.count, l.p50_ms, l.p90_ms, l.p99_ms, l.max_ms
+            );
+        }
+    }
+    if let (Some(avg_rank), Some(best), Some(hp)) =
+        (summary.avg_rank, summary.best_rank, summary.avg_final_hp)
+    {
+        println!(
+            "wins: {}  avg rank: {:.1}  best rank: {}  avg final hp: {:.1}",
+            summary.wins, avg_rank, best, hp
+        );
+    }
+    if let Some(v) = &summary.verification {
+        println!(
+            "verified: {}  mismatched: {}  unverifiable: {}",
+            v.verified, v.mismatched, v.unverifiable
+        );
+        for s in reports.iter().flat_map(|r| &r.samples) {
+            let (Outcome::Finished(res), Some(Verification::Mismatch(mismatches))) =
+                (&s.outcome, &s.verification)
+            else {
+                continue;
+            };
+            for m in mismatches {
+                let detail = match m {
+                    Mismatch::Stats {
+                        sent, logged: None, ..
+                    } => format!("sent hp {} / atk {}, not in roster", sent.0, sent.1),
+                    Mismatch::Stats {
+                        sent,
+                        logged: Some(logged),
+                        ..
+                    } => format!(
+                        "sent hp {} / atk {} / {}, logged hp {} / atk {} / {}",
+                        sent.0,
+                        sent.1,
+                        sent.2.as_str(),
+                        logged.0,
+                        logged.1,
+                        logged.2.as_str()
+                    ),
+                    Mismatch::Result { server, local, .. } => format!(
+                        "server rank {} / hp {}, local rank {} / hp {}",
+                        server.0, server.1, local.0, local.1
+                    ),
+                };
+                println!(
+                    "  mismatch in match #{}: {} {}",
+                    res.match_id.unwrap_or_default(),
+                    m.name(),
+                    detail
+                );
+            }
+        }
+    }
+}
+
+// ===== プレイヤー =====
+
+struct Bot {
+    client: reqwest::Client,
+    base: String, // http://host:port/v1
+    args: Args,
+    budget: StatBudget,
+}
+
+impl Bot {
+    /// 409（登録済み）ならそのままログインする
+    async fn login(&self, name: &str) -> Result<String, String> {
+        let creds = CredentialsRequest {
+            name: name.to_string(),
+            password: self.args.password.clone(),
+        };
+        let register = self.post_json("register", &creds).await?;
+        if register.status().is_success() {
+            return read_token(register).await;
+        }
+        if register.status() != reqwest::StatusCode::CONFLICT {
+            return Err(error_text(register).await);
+        }
+        let login = self.post_json("login", &creds).await?;
+        if login.status().is_success() {
+            read_token(login).await
+        } else {
+            Err(error_text(login).await)
+        }
+    }
+
+    async fn post_json<T: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &T,
+    ) -> Result<reqwest::Response, String> {
+        self.client
+            .post(format!("{}/{}", self.base, endpoint))
+            .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
+            .timeout(Duration::from_secs(self.args.request_timeout_secs.max(1)))
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| describe_error(&e))
+    }
+
+    /// --hp / --atk が無ければ予算を hp と atk にランダムに振り分ける
+    fn character(&self) -> (i32, i32) {
+        let points = self.budget.points.max(0);
+        let hp_points = rand::thread_rng().gen_range(0..=points);
+        let (hp, atk) = self.budget.stats(hp_points, points - hp_points);
+        (self.args.hp.unwrap_or(hp), self.args.atk.unwrap_or(atk))
+    }
+
+    async fn join(&self, name: &str, token: &str) -> Sample {
+        let (hp, atk) = self.character();
+        let req = JoinRequest {
+            name: name.to_string(),
+            hp,
+            atk,
+            ticket: None,
+            class: self.args.class,
+            targeting: self.args.targeting,
+            mode: self.args.mode,
+            lobby: None,
+            squad: Vec::new(),
+        };
+
+        let started = Instant::now();
+        let mut throttled = 0;
+        let outcome = loop {
+            let resp = self
+                .client
+                .post(format!("{}/join", self.base))
+                .bearer_auth(token)
+                .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
+                .timeout(Duration::from_secs(self.args.join_timeout_secs.max(1)))
+                .json(&req)
+                .send()
+                .await;
+            let resp = match resp {
+                Ok(resp) => resp,
+                Err(e) => break Outcome::Network(describe_error(&e)),
+            };
+            let status = resp.status();
+            if status.is_success() {
+                break match resp.json::<JoinResponse>().await {
+                    Ok(res) => Outcome::Finished(res),
+                    Err(e) => Outcome::Network(format!("JSON parse error: {}", e)),
+                };
+            }
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS && throttled < self.args.max_retries
+            {
+                throttled += 1;
+                let wait = resp
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse::<u64>().ok())
+                    .unwrap_or(1);
+                tokio::time::sleep(Duration::from_secs(wait.clamp(1, 60))).await;
+                continue;
+            }
+            break Outcome::Rejected(error_code(resp).await);
+        };
+        let latency = started.elapsed();
+
+        let verification = match &outcome {
+            Outcome::Finished(res) if self.args.verify => Some(self.verify(&req, res).await),
+            _ => None,
+        };
+        Sample {
+            latency,
+            throttled,
+            outcome,
+            verification,
+        }
+    }
+
+    /// ログが取れない・seed が無いなら Unverifiable
+    async fn verify(&self, req: &JoinRequest, res: &JoinResponse) -> Verification {
+        let Some(id) = res.match_id else {
+            return Verification::Unverifiable;
+        };
+        let log = self
+            .client
+            .get(format!("{}/matches/{}/log", self.base, id))
+            .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
+            .timeout(Duration::from_secs(self.args.request_timeout_secs.max(1)))
+            .send()
+            .await;
+        match log {
+            Ok(resp) if resp.status().is_success() => match resp.json::<BattleLog>().await {
+                Ok(log) => engine::verify(req, res, &log),
+                Err(_) => Verification::Unverifiable,
+            },
+            _ => Verification::Unverifiable,
+        }
+    }
+
+    async fn run_player(self: Arc<Self>, index: usize, start_at: Instant) -> PlayerReport {
+        tokio::time::sleep_until(start_at.into()).await;
+
+        let mut report = PlayerReport::default();
+        let name = format!("{}_{:04}", self.args.name_prefix, index + 1);
+        let started = Instant::now();
+        let token = match self.login(&name).await {
+            Ok(token) => token,
+            Err(e) => {
+                report.login_error = Some(e);
+                return report;
+            }
+        };
+        report.login_latency = Some(started.elapsed());
+
+        for _ in 0..self.args.rounds {
+            let sample = self.join(&name, &token).await;
+            // トークンが切れたなどで続けても同じ結果になる
+            let stop = matches!(&sample.outcome, Outcome::Rejected(code) if code == "unauthorized");
+            report.samples.push(sample);
+            if stop {
+                break;
+            }
+        }
+        report
+    }
+}
+
+/// reqwest のエラーは原因（接続拒否など）が source 側にあるのでつなげて出す
+fn describe_error(e: &dyn std::error::Error) -> String {
+    let mut text = e.to_string();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        text.push_str(": ");
+        text.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    text
+}
+
+async fn read_token(resp: reqwest::Response) -> Result<String, String> {
+    resp.json::<TokenResponse>()
+        .await
+        .map(|t| t.token)
+        .map_err(|e| format!("JSON parse error: {}", e))
+}
+
+/// ErrorBody があれば code、無ければ HTTP ステータス
+async fn error_code(resp: reqwest::Response) -> String {
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    serde_json::from_str::<ErrorBody>(&body)
+        .map(|e| e.code)
+        .unwrap_or_else(|_| format!("http_{}", status.as_u16()))
+}
+
+async fn error_text(resp: reqwest::Response) -> String {
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    match serde_json::from_str::<ErrorBody>(&body) {
+        Ok(e) => format!("HTTP {} [{}] {}", status.as_u16(), e.code, e.message),
+        Err(_) => format!("HTTP {}: {}", status, body),
+    }
+}
+
+// ===== 実行 =====
+
+async fn run(args: Args) -> Result<(), String> {
+    let server = args.server.trim().trim_end_matches('/').to_string();
+    let base = format!("{}{}", server, API_PREFIX);
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(5))
+        // 全員が同じサーバに繋ぐので接続を使い回す
+        .pool_max_idle_per_host(args.players)
+        .build()
+        .map_err(|e| format!("failed to build HTTP client: {}", e))?;
+
+    let info: ServerInfo = client
+        .get(format!("{}/version", base))
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| format!("could not reach {}: {}", server, e))?
+        .json()
+        .await
+        .map_err(|e| format!("unexpected /version response: {}", e))?;
+    if info.min_protocol_version > PROTOCOL_VERSION {
+        return Err(format!(
+            "server requires protocol {} or newer (this bot speaks {})",
+            info.min_protocol_version, PROTOCOL_VERSION
+        ));
+    }
+    let budget = info
+        .stat_budget
+        .ok_or("matchmaking is disabled on this server")?;
+    if args.verify && !info.supports(capability::VERIFY) {
+        eprintln!(
+            "warning: the server does not publish battle seeds, results will be unverifiable"
+        );
+    }
+
+    let format = args.format;
+    let interval = if args.rate > 0.0 {
+        Duration::from_secs_f64(1.0 / args.rate)
+    } else {
+        Duration::ZERO
+    };
+    eprintln!(
+        "{} players x {} rounds -> {} (server v{})",
+        args.players, args.rounds, server, info.server_version
+    );
+
+    let bot = Arc::new(Bot {
+        client,
+        base,
+        args,
+        budget,
+    });
+    let started = Instant::now();
+    let handles: Vec<_> = (0..bot.args.players)
+        .map(|i| {
+            let start_at = started + interval.mul_f64(i as f64);
+            tokio::spawn(bot.clone().run_player(i, start_at))
+        })
+        .collect();
+
+    let mut reports = Vec::with_capacity(handles.len());
+    for handle in handles {
+        reports.push(
+            handle
+                .await
+                .map_err(|e| format!("player task failed: {}", e))?,
+        );
+    }
+    let summary = summarize(&reports, started.elapsed());
+
+    match format {
+        Format::Text => print_text(&summary, &reports),
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&summary).map_err(|e| e.to_string())?
+        ),
+    }
+    // 食い違いは終了コードでも知らせる
+    match summary.verification {
+        Some(v) if v.mismatched > 0 => Err(format!(
+            "{} result(s) did not match a local re-run",
+            v.mismatched
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Args::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("error: {}", msg);
+            ExitCode::from(2)
+        }
+    }
+}
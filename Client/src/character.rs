@@ -0,0 +1,253 @@
+use crate::AppState;
+use battle_api::{t, CharacterClass, StatBudget, Targeting};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+// ===== キャラクター作成 =====
+//
+// サーバが /v1/version で公開する予算（StatBudget）の範囲でポイントを振る。
+// 送るのはクラス補正の前の hp / atk で、補正はサーバがかける（プレビューも同じ計算）。
+
+/// ポイントの振り方とクラス・狙い方。名前を付けてプリセットとして保存できる
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub hp_points: i32,
+    pub atk_points: i32,
+    #[serde(default)]
+    pub class: CharacterClass,
+    #[serde(default)]
+    pub targeting: Targeting,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        let half = StatBudget::default().points / 2;
+        Self {
+            name: "default".to_string(),
+            hp_points: half,
+            atk_points: half,
+            class: CharacterClass::default(),
+            targeting: Targeting::default(),
+        }
+    }
+}
+
+impl Preset {
+    /// 予算が減ったサーバに繋いだときは atk から削って収める
+    pub fn clamp_to(&mut self, budget: &StatBudget) {
+        let points = budget.points.max(0);
+        self.hp_points = self.hp_points.clamp(0, points);
+        self.atk_points = self.atk_points.clamp(0, points - self.hp_points);
+    }
+
+    /// サーバに送る値（クラス補正の前）
+    pub fn base_stats(&self, budget: &StatBudget) -> (i32, i32) {
+        budget.stats(self.hp_points, self.atk_points)
+    }
+
+    /// 実際に戦うときの値（クラス補正の後）
+    pub fn derived_stats(&self, budget: &StatBudget) -> (i32, i32) {
+        let (hp, atk) = self.base_stats(budget);
+        self.class.derive_stats(hp, atk)
+    }
+
+    pub fn summary(&self, budget: &StatBudget) -> String {
+        let (hp, atk) = self.derived_stats(budget);
+        format!(
+            "HP {} / ATK {} ({}, {})",
+            hp,
+            atk,
+            self.class.as_str(),
+            self.targeting.as_str()
+        )
+    }
+}
+
+impl AppState {
+    /// 接続先の予算（古いサーバ・未確認なら battle_api のデフォルト）
+    pub(crate) fn stat_budget(&self) -> StatBudget {
+        self.server_info
+            .as_ref()
+            .and_then(|info| info.stat_budget)
+            .unwrap_or_default()
+    }
+
+    /// 同じ名前のプリセットは上書きする
+    fn save_preset(&mut self) {
+        let name = self.preset_name.trim().to_string();
+        if name.is_empty() {
+            self.status = t!("status-preset-name-empty");
+            return;
+        }
+        let preset = Preset {
+            name: name.clone(),
+            ..self.character.clone()
+        };
+        match self.presets.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+        self.character.name = name.clone();
+        self.status = t!("status-preset-saved", name = name);
+    }
+
+    fn load_preset(&mut self, index: usize) {
+        let Some(preset) = self.presets.get(index) else {
+            return;
+        };
+        self.character = preset.clone();
+        self.preset_name = preset.name.clone();
+        self.status = t!("status-preset-loaded", name = preset.name.as_str());
+    }
+
+    pub(crate) fn ui_character(&mut self, ui: &mut egui::Ui) {
+        let budget = self.stat_budget();
+        self.character.clamp_to(&budget);
+
+        let used = self.character.hp_points + self.character.atk_points;
+        ui.label(t!(
+            "stat-points",
+            used = used,
+            points = budget.points,
+            base_hp = budget.base_hp,
+            base_atk = budget.base_atk
+        ));
+
+        let hp_max = budget.points - self.character.atk_points;
+        ui.add(
+            egui::Slider::new(&mut self.character.hp_points, 0..=hp_max)
+                .text(t!("slider-hp", per_point = budget.hp_per_point)),
+        );
+        let atk_max = budget.points - self.character.hp_points;
+        ui.add(
+            egui::Slider::new(&mut self.character.atk_points, 0..=atk_max)
+                .text(t!("slider-atk", per_point = budget.atk_per_point)),
+        );
+
+        egui::ComboBox::from_label(t!("label-class"))
+            .selected_text(self.character.class.as_str())
+            .show_ui(ui, |ui| {
+                for class in CharacterClass::ALL {
+                    ui.selectable_value(&mut self.character.class, class, class.as_str());
+                }
+            });
+        egui::ComboBox::from_label(t!("label-targeting"))
+            .selected_text(self.character.targeting.as_str())
+            .show_ui(ui, |ui| {
+                for targeting in Targeting::ALL {
+                    ui.selectable_value(
+                        &mut self.character.targeting,
+                        targeting,
+                        targeting.as_str(),
+                    );
+                }
+            });
+
+        ui.add_space(8.0);
+        ui.label(t!("label-preview"));
+        let (base_hp, base_atk) = self.character.base_stats(&budget);
+        let (hp, atk) = self.character.derived_stats(&budget);
+        ui.monospace(format!("HP  : {} -> {}", base_hp, hp));
+        ui.monospace(format!("ATK : {} -> {}", base_atk, atk));
+
+        ui.add_space(8.0);
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(t!("label-preset-name"));
+            ui.text_edit_singleline(&mut self.preset_name);
+            if ui.button(t!("button-save")).clicked() {
+                self.save_preset();
+            }
+        });
+
+        let mut load = None;
+        let mut delete = None;
+        let mut add_to_squad = None;
+        for (i, preset) in self.presets.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.monospace(&preset.name);
+                ui.label(preset.summary(&budget));
+                if ui.small_button(t!("button-load")).clicked() {
+                    load = Some(i);
+                }
+                if ui.small_button(t!("button-add-to-squad")).clicked() {
+                    add_to_squad = Some(preset.clone());
+                }
+                if ui.small_button(t!("button-delete")).clicked() {
+                    delete = Some(i);
+                }
+            });
+        }
+        if self.presets.is_empty() {
+            ui.small(t!("presets-none"));
+        }
+        if let Some(i) = load {
+            self.load_preset(i);
+        }
+        if let Some(i) = delete {
+            let removed = self.presets.remove(i);
+            self.status = t!("status-preset-deleted", name = removed.name);
+        }
+        if let Some(preset) = add_to_squad {
+            self.add_to_squad(preset);
+        }
+
+        ui.add_space(8.0);
+        ui.separator();
+        self.ui_squad(ui, &budget);
+
+        ui.add_space(12.0);
+        ui.label(t!("label-status", status = self.status.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(hp_points: i32, atk_points: i32) -> Preset {
+        Preset {
+            hp_points,
+            atk_points,
+            ..Preset::default()
+        }
+    }
+
+    fn budget(points: i32) -> StatBudget {
+        StatBudget {
+            points,
+            ..StatBudget::default()
+        }
+    }
+
+    #[test]
+    fn clamp_to_keeps_presets_that_fit() {
+        let mut p = preset(10, 20);
+        p.clamp_to(&budget(40));
+        assert_eq!((p.hp_points, p.atk_points), (10, 20));
+    }
+
+    #[test]
+    fn clamp_to_takes_from_atk_first() {
+        let mut p = preset(20, 20);
+        p.clamp_to(&budget(30));
+        assert_eq!((p.hp_points, p.atk_points), (20, 10));
+
+        let mut p = preset(35, 5);
+        p.clamp_to(&budget(30));
+        assert_eq!((p.hp_points, p.atk_points), (30, 0));
+    }
+
+    #[test]
+    fn clamp_to_handles_negative_points() {
+        let mut p = preset(-5, -5);
+        p.clamp_to(&budget(40));
+        assert_eq!((p.hp_points, p.atk_points), (0, 0));
+
+        // 予算が負のサーバでも 0 にするだけ
+        let mut p = preset(10, 10);
+        p.clamp_to(&budget(-1));
+        assert_eq!((p.hp_points, p.atk_points), (0, 0));
+    }
+}
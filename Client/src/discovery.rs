@@ -0,0 +1,129 @@
+use crate::servers::ServerProfile;
+use crate::{AppState, ClientEvent};
+use battle_api::{mdns, t};
+use eframe::egui;
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+// ===== mDNS でのサーバ探索（mdns feature） =====
+//
+// battle_server の mdns feature が広告する mdns::SERVICE_TYPE を数秒だけ探す。
+// mdns-sd の受信は同期 API なので、ランタイムではなく専用のスレッドで待つ。
+
+/// これだけ探したら打ち切る
+const BROWSE_TIME: Duration = Duration::from_secs(3);
+
+/// LAN で見つけたサーバ
+#[derive(Clone, Debug)]
+pub struct DiscoveredServer {
+    pub name: String,
+    pub url: String,
+    pub version: String,
+}
+
+pub fn browse(tx: mpsc::Sender<(String, ClientEvent)>) {
+    std::thread::spawn(move || {
+        let result = browse_blocking(&tx);
+        let _ = tx.send((String::new(), ClientEvent::DiscoveryFinished(result)));
+    });
+}
+
+fn browse_blocking(tx: &mpsc::Sender<(String, ClientEvent)>) -> Result<(), String> {
+    let daemon = ServiceDaemon::new().map_err(|e| e.to_string())?;
+    let events = daemon
+        .browse(mdns::SERVICE_TYPE)
+        .map_err(|e| e.to_string())?;
+
+    let deadline = Instant::now() + BROWSE_TIME;
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        let Ok(event) = events.recv_timeout(left) else {
+            break;
+        };
+        let ServiceEvent::ServiceResolved(info) = event else {
+            continue;
+        };
+        // IPv4 を優先する（IPv6 だと URL に [] が要る）
+        let Some(addr) = info
+            .get_addresses_v4()
+            .into_iter()
+            .next()
+            .map(|ip| ip.to_string())
+            .or_else(|| {
+                info.get_addresses()
+                    .iter()
+                    .next()
+                    .map(|ip| format!("[{}]", ip))
+            })
+        else {
+            continue;
+        };
+        let name = info
+            .get_fullname()
+            .trim_end_matches(mdns::SERVICE_TYPE)
+            .trim_end_matches('.')
+            .to_string();
+        let server = DiscoveredServer {
+            name,
+            url: format!("http://{}:{}", addr, info.get_port()),
+            version: info
+                .get_property_val_str(mdns::TXT_VERSION)
+                .unwrap_or("?")
+                .to_string(),
+        };
+        let _ = tx.send((String::new(), ClientEvent::Discovered(server)));
+    }
+
+    let _ = daemon.shutdown();
+    Ok(())
+}
+
+impl AppState {
+    pub(crate) fn add_discovered(&mut self, server: DiscoveredServer) {
+        let known = self.discovered.iter().any(|d| d.url == server.url)
+            || self.profiles.iter().any(|p| p.url == server.url);
+        if !known {
+            self.discovered.push(server);
+        }
+    }
+
+    pub(crate) fn ui_discovery(&mut self, ui: &mut egui::Ui) {
+        let label = if self.discovering {
+            t!("button-discovering")
+        } else {
+            t!("button-discover")
+        };
+        if ui
+            .add_enabled(!self.discovering, egui::Button::new(label))
+            .clicked()
+        {
+            self.discovering = true;
+            self.discovered.clear();
+            browse(self.tx.clone());
+        }
+
+        let mut add = None;
+        for (i, d) in self.discovered.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(&d.name);
+                ui.monospace(&d.url);
+                ui.small(format!("v{}", d.version));
+                if ui.small_button(t!("button-add")).clicked() {
+                    add = Some(i);
+                }
+            });
+        }
+        if let Some(i) = add {
+            let d = self.discovered.remove(i);
+            if !self.profiles.iter().any(|p| p.name == d.name) {
+                self.profiles.push(ServerProfile {
+                    name: d.name.clone(),
+                    url: d.url.clone(),
+                    session: None,
+                });
+            }
+            self.status = t!("status-server-added", name = d.name.as_str());
+            self.ping(&d.url);
+        }
+    }
+}
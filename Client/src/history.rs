@@ -0,0 +1,228 @@
+use crate::{AppState, PendingJoin};
+use battle_api::{t, JoinResponse};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ===== 対戦履歴 =====
+//
+// /v1/join の結果を手元に残す（eframe のストレージに保存して再起動後も読む）。
+// グラフは egui の painter で直接描く。
+
+/// 保存しておく件数（古いものから消す）
+const MAX_HISTORY: usize = 500;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub finished_at: u64, // UNIX 秒（手元の時計）
+    pub server_url: String,
+    pub name: String,
+    pub rank: usize,
+    pub final_hp: i32,
+    pub is_winner: bool,
+    /// 参加したときのキャラクター（"HP 120 / ATK 25 (fighter, random)" など）
+    #[serde(default)]
+    pub character: String,
+}
+
+impl AppState {
+    pub(crate) fn record_result(&mut self, result: &JoinResponse, join: PendingJoin) {
+        let finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.history.push(HistoryEntry {
+            finished_at,
+            server_url: join.server_url,
+            name: result.name.clone(),
+            rank: result.rank,
+            final_hp: result.final_hp,
+            is_winner: result.is_winner,
+            character: join.character,
+        });
+        if self.history.len() > MAX_HISTORY {
+            let excess = self.history.len() - MAX_HISTORY;
+            self.history.drain(..excess);
+        }
+    }
+
+    pub(crate) fn ui_history(&mut self, ui: &mut egui::Ui) {
+        if self.history.is_empty() {
+            ui.label(t!("history-none"));
+            return;
+        }
+
+        let wins = self.history.iter().filter(|e| e.is_winner).count();
+        let avg_rank =
+            self.history.iter().map(|e| e.rank).sum::<usize>() as f32 / self.history.len() as f32;
+        let best = self
+            .history
+            .iter()
+            .map(|e| e.rank)
+            .min()
+            .unwrap_or_default();
+        ui.horizontal(|ui| {
+            ui.label(t!(
+                "history-summary",
+                matches = self.history.len(),
+                wins = wins,
+                best = best,
+                avg = format!("{:.1}", avg_rank)
+            ));
+            if ui.small_button(t!("button-clear")).clicked() {
+                self.history.clear();
+                self.status = t!("status-history-cleared");
+            }
+        });
+        if self.history.is_empty() {
+            return;
+        }
+
+        ui.add_space(4.0);
+        ui.label(t!("history-rank-chart"));
+        let ranks: Vec<f32> = self.history.iter().map(|e| e.rank as f32).collect();
+        line_chart(ui, &ranks, true);
+
+        ui.label(t!("history-hp-chart"));
+        let hps: Vec<f32> = self.history.iter().map(|e| e.final_hp as f32).collect();
+        line_chart(ui, &hps, false);
+
+        ui.add_space(4.0);
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .max_height(160.0)
+            .show(ui, |ui| {
+                egui::Grid::new("history")
+                    .striped(true)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        ui.strong(t!("col-date"));
+                        ui.strong(t!("col-rank"));
+                        ui.strong(t!("col-hp"));
+                        ui.strong(t!("col-character"));
+                        ui.strong(t!("col-server"));
+                        ui.end_row();
+
+                        // 新しい順
+                        for e in self.history.iter().rev() {
+                            ui.monospace(format_date(e.finished_at));
+                            let rank = if e.is_winner {
+                                t!("history-rank-win", rank = e.rank)
+                            } else {
+                                e.rank.to_string()
+                            };
+                            ui.monospace(rank);
+                            ui.monospace(e.final_hp.to_string());
+                            ui.label(&e.character);
+                            ui.small(&e.server_url);
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
+
+// ===== グラフ =====
+
+/// 古い順の値を折れ線で描く。invert なら小さい値を上にする（順位用）
+fn line_chart(ui: &mut egui::Ui, values: &[f32], invert: bool) {
+    let width = ui.available_width().max(100.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, 100.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let span = (max - min).max(1.0);
+
+    let plot = rect.shrink2(egui::vec2(36.0, 8.0));
+    let to_pos = |i: usize, v: f32| {
+        let x = if values.len() > 1 {
+            plot.left() + plot.width() * i as f32 / (values.len() - 1) as f32
+        } else {
+            plot.center().x
+        };
+        let t = (v - min) / span;
+        let t = if invert { t } else { 1.0 - t };
+        egui::pos2(x, plot.top() + plot.height() * t)
+    };
+
+    // 目盛り（上端と下端の値）
+    let text_color = visuals.weak_text_color();
+    let font = egui::FontId::monospace(10.0);
+    let (top, bottom) = if invert { (min, max) } else { (max, min) };
+    painter.text(
+        egui::pos2(rect.left() + 4.0, plot.top()),
+        egui::Align2::LEFT_CENTER,
+        format!("{}", top),
+        font.clone(),
+        text_color,
+    );
+    painter.text(
+        egui::pos2(rect.left() + 4.0, plot.bottom()),
+        egui::Align2::LEFT_CENTER,
+        format!("{}", bottom),
+        font,
+        text_color,
+    );
+
+    let stroke = egui::Stroke::new(1.5, visuals.selection.bg_fill);
+    let points: Vec<egui::Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| to_pos(i, v))
+        .collect();
+    painter.add(egui::Shape::line(points.clone(), stroke));
+    for p in points {
+        painter.circle_filled(p, 2.5, stroke.color);
+    }
+}
+
+/// UNIX 秒を "YYYY-MM-DD HH:MM"（UTC）にする
+fn format_date(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // 1970-01-01 からの日数を年月日にする（Howard Hinnant の civil_from_days）
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_date_matches_the_calendar() {
+        let cases = [
+            (0, "1970-01-01 00:00"),
+            // うるう日
+            (951_827_696, "2000-02-29 12:34"),
+            (1_735_689_599, "2024-12-31 23:59"),
+            // 2100 年は 4 で割れるがうるう年ではない
+            (4_107_542_340, "2100-02-28 23:59"),
+            (4_107_542_400, "2100-03-01 00:00"),
+            (253_402_300_799, "9999-12-31 23:59"),
+        ];
+        for (secs, expected) in cases {
+            assert_eq!(format_date(secs), expected, "{}", secs);
+        }
+    }
+}
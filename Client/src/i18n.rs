@@ -0,0 +1,78 @@
+use battle_api::i18n::{self, Catalog, Locale};
+use battle_api::t;
+use eframe::egui;
+
+// ===== 表示言語 =====
+//
+// 文言は locales/*.ftl。言語は保存した設定 → BATTLE_LANG / LANG の順に決め、画面から切り替えられる。
+// egui の既定フォントには日本語が無いので、OS の CJK フォントを探して後ろに足す。
+
+pub const CATALOG: Catalog = &[
+    (Locale::En, include_str!("../locales/en.ftl")),
+    (Locale::Ja, include_str!("../locales/ja.ftl")),
+];
+
+/// 明示的に指定するフォント（.ttf / .otf / .ttc）
+const FONT_ENV: &str = "BATTLE_CJK_FONT";
+
+/// よくある置き場所（Linux、Windows、macOS の順）
+const FONT_PATHS: &[&str] = &[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/opentype/source-han-sans/SourceHanSans-Regular.ttc",
+    "/usr/share/fonts/adobe-source-han-sans/SourceHanSans-Regular.ttc",
+    "/usr/share/fonts/truetype/fonts-japanese-gothic.ttf",
+    "C:\\Windows\\Fonts\\YuGothM.ttc",
+    "C:\\Windows\\Fonts\\meiryo.ttc",
+    "C:\\Windows\\Fonts\\msgothic.ttc",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+];
+
+pub fn init(locale: Locale) {
+    i18n::init(CATALOG, locale);
+}
+
+/// 見つかった最初の CJK フォントを全てのフォントファミリーの予備にする
+pub fn install_cjk_font(ctx: &egui::Context) {
+    let env = std::env::var(FONT_ENV).ok().filter(|p| !p.is_empty());
+    let Some(bytes) = env
+        .iter()
+        .map(String::as_str)
+        .chain(FONT_PATHS.iter().copied())
+        .find_map(|path| std::fs::read(path).ok())
+    else {
+        eprintln!("{}", t!("font-not-found"));
+        return;
+    };
+
+    let mut fonts = egui::FontDefinitions::default();
+    fonts
+        .font_data
+        .insert("cjk".to_string(), egui::FontData::from_owned(bytes));
+    for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .push("cjk".to_string());
+    }
+    ctx.set_fonts(fonts);
+}
+
+/// 言語の切り替え（次の描画から反映される）
+pub fn ui_language(ui: &mut egui::Ui) {
+    let mut locale = i18n::locale();
+    egui::ComboBox::from_label(t!("label-language"))
+        .selected_text(locale.native_name())
+        .show_ui(ui, |ui| {
+            for l in Locale::ALL {
+                ui.selectable_value(&mut locale, l, l.native_name());
+            }
+        });
+    i18n::set_locale(locale);
+}
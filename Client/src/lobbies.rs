@@ -0,0 +1,195 @@
+use crate::{accept_header, decode_body, error_response, net, AppState, ClientEvent};
+use battle_api::{
+    capability, t, GameMode, LobbyInfo, API_PREFIX, PROTOCOL_HEADER, PROTOCOL_VERSION,
+};
+use eframe::egui;
+use net::{Failure, RetryPolicy};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+// ===== ロビー一覧 =====
+//
+// GET /v1/lobbies を定期的に取り直して、締め切り前のロビーを選んで入れるようにする。
+// lobbies に対応していないサーバでは Quick join（mode・ロビーの指定なし）だけ使う。
+
+/// ロビー画面を開いている間はこの間隔で取り直す
+const REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+
+impl AppState {
+    fn supports_lobbies(&self) -> bool {
+        self.server_info
+            .as_ref()
+            .is_some_and(|info| info.supports(capability::LOBBIES))
+    }
+
+    pub(crate) fn refresh_lobbies(&mut self) {
+        if self.lobbies_fetch.is_some() {
+            return;
+        }
+        let Ok(server_url) = self.normalized_server_url() else {
+            return;
+        };
+        let cancel = CancellationToken::new();
+        self.lobbies_fetch = Some(cancel.clone());
+        self.lobbies_fetched_at = Some(Instant::now());
+
+        let client = self.net.client();
+        let settings = self.net.settings().clone();
+        let tx = self.tx.clone();
+
+        self.net.spawn(async move {
+            let url = format!("{}{}/lobbies", server_url, API_PREFIX);
+            let resp = net::send_with_retry(
+                || {
+                    client
+                        .get(&url)
+                        .header(reqwest::header::ACCEPT, accept_header())
+                        .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
+                        .timeout(settings.request_timeout())
+                },
+                RetryPolicy::Idempotent,
+                settings.max_retries,
+                &cancel,
+                // 一覧の取り直しは状態欄に出さない
+                |_| {},
+            )
+            .await;
+            let result = match resp {
+                Ok(r) if r.status().is_success() => decode_body::<Vec<LobbyInfo>>(r).await,
+                Ok(r) => Err(error_response(r).await),
+                Err(Failure::Network(msg)) => Err(msg),
+                Err(Failure::Cancelled) => return,
+            };
+            let _ = tx.send((
+                String::new(),
+                ClientEvent::LobbiesLoaded(server_url, result),
+            ));
+        });
+    }
+
+    pub(crate) fn apply_lobbies(&mut self, url: String, result: Result<Vec<LobbyInfo>, String>) {
+        self.lobbies_fetch = None;
+        // 取得中に URL が変わっていたら捨てる
+        if self.normalized_server_url().ok() != Some(url) {
+            self.lobbies.clear();
+            return;
+        }
+        match result {
+            Ok(lobbies) => self.lobbies = lobbies,
+            Err(msg) => {
+                self.lobbies.clear();
+                self.status = t!("status-lobbies-failed", message = msg);
+            }
+        }
+    }
+
+    /// ログアウト・サーバの切り替えで一覧を捨てる
+    pub(crate) fn clear_lobbies(&mut self) {
+        if let Some(fetch) = self.lobbies_fetch.take() {
+            fetch.cancel();
+        }
+        self.lobbies.clear();
+        self.lobbies_fetched_at = None;
+    }
+
+    /// join ボタンを押せるか（マッチングを無効にしているサーバでは押せない）
+    pub(crate) fn can_join(&self) -> bool {
+        let matchmaking = self
+            .server_info
+            .as_ref()
+            .is_none_or(|info| info.supports(capability::MATCHMAKING));
+        !self.waiting() && !self.client_too_old && matchmaking
+    }
+
+    pub(crate) fn ui_lobby_browser(&mut self, ui: &mut egui::Ui) {
+        if !self.supports_lobbies() {
+            ui.small(t!("lobbies-single"));
+            return;
+        }
+
+        let due = self
+            .lobbies_fetched_at
+            .is_none_or(|at| at.elapsed() >= REFRESH_INTERVAL);
+        if due {
+            self.refresh_lobbies();
+        }
+        ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+
+        let can_join = self.can_join();
+        ui.horizontal(|ui| {
+            ui.label(t!("label-mode"));
+            egui::ComboBox::from_id_source("join_mode")
+                .selected_text(self.join_mode.as_str())
+                .show_ui(ui, |ui| {
+                    for mode in GameMode::ALL {
+                        ui.selectable_value(&mut self.join_mode, mode, mode.as_str());
+                    }
+                });
+            if ui
+                .add_enabled(can_join, egui::Button::new(t!("button-join-mode")))
+                .on_hover_text(t!("hover-join-mode"))
+                .clicked()
+            {
+                self.join(self.join_mode, None);
+            }
+            if ui
+                .add_enabled(
+                    self.lobbies_fetch.is_none(),
+                    egui::Button::new(t!("button-refresh")),
+                )
+                .clicked()
+            {
+                self.refresh_lobbies();
+            }
+        });
+
+        if self.lobbies.is_empty() {
+            ui.small(t!("lobbies-none"));
+            return;
+        }
+
+        let size = if self.supports_squad() {
+            self.squad_size()
+        } else {
+            1
+        };
+        let mut join = None;
+        egui::Grid::new("lobbies")
+            .striped(true)
+            .num_columns(5)
+            .show(ui, |ui| {
+                ui.strong("#");
+                ui.strong(t!("col-mode"));
+                ui.strong(t!("col-players"));
+                ui.strong(t!("col-starts-in"));
+                ui.label("");
+                ui.end_row();
+                for l in &self.lobbies {
+                    ui.monospace(l.id.to_string());
+                    ui.label(l.mode.as_str())
+                        .on_hover_text(t!("hover-battle-size", count = l.battle_size));
+                    ui.monospace(format!("{} / {}", l.players, l.capacity))
+                        .on_hover_text(t!("hover-max-squad", count = l.max_squad));
+                    ui.monospace(format!("{}s", l.seconds_left));
+                    // squad が入りきらないロビーには入れない
+                    let fits = size <= l.max_squad && l.players + size <= l.capacity;
+                    if ui
+                        .add_enabled(can_join && fits, egui::Button::new(t!("button-join")))
+                        .clicked()
+                    {
+                        // lobby を指定するとサーバは mode を見ないので、知らない mode のロビーにも入れる
+                        let mode = match l.mode {
+                            GameMode::Unknown => GameMode::default(),
+                            mode => mode,
+                        };
+                        join = Some((mode, l.id));
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if let Some((mode, id)) = join {
+            self.join(mode, Some(id));
+        }
+    }
+}
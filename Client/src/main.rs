@@ -1,20 +1,111 @@
+mod arena;
+mod character;
+#[cfg(feature = "mdns")]
+mod discovery;
+mod history;
+mod i18n;
+mod lobbies;
+mod net;
+mod servers;
+mod squad;
+mod verify;
+
+use arena::Arena;
+use battle_api::engine::Verification;
+use battle_api::roster::RosterFormat;
+use battle_api::{
+    capability, mime, BattleLog, CredentialsRequest, ErrorBody, GameMode, JoinRequest,
+    JoinResponse, LobbyInfo, ServerInfo, TokenResponse, API_PREFIX, PROTOCOL_HEADER,
+    PROTOCOL_VERSION,
+};
+use battle_api::{i18n::Locale, t};
+use character::Preset;
 use eframe::egui;
-use serde::{Deserialize, Serialize};
+use history::HistoryEntry;
+use net::{Failure, Net, NetSettings, RetryPolicy, Retrying};
+use serde::de::DeserializeOwned;
+use servers::{Ping, ServerProfile};
+use std::collections::HashMap;
 use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// /v1/register, /v1/login のレスポンス。そのまま保存してログイン状態を復元する
+type Session = TokenResponse;
+
+const SESSION_KEY: &str = "session";
+const SERVER_URL_KEY: &str = "server_url"; // トークンを発行したサーバ
+const PLAYER_NAME_KEY: &str = "player_name";
+const ROSTER_PATH_KEY: &str = "roster_path";
+const CHARACTER_KEY: &str = "character";
+const PRESETS_KEY: &str = "presets";
+const SQUAD_KEY: &str = "squad";
+const HISTORY_KEY: &str = "history";
+const NET_SETTINGS_KEY: &str = "net_settings";
+const PROFILES_KEY: &str = "server_profiles";
+const ACTIVE_PROFILE_KEY: &str = "active_profile";
+const LOCALE_KEY: &str = "locale";
+
+/// 前後の空白と末尾の / を取り、スキームが無ければ http:// を付ける
+fn normalize_url(url: &str) -> Result<String, String> {
+    let mut url = url.trim().trim_end_matches('/').to_string();
+    if url.is_empty() {
+        return Err(t!("url-empty"));
+    }
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        url = format!("http://{url}");
+    }
+    if let Err(e) = reqwest::Url::parse(&url) {
+        return Err(t!("url-invalid", error = e.to_string()));
+    }
+    Ok(url)
+}
+
+/// 結果は MessagePack を優先して受け取る（対応していないサーバは JSON で返す）
+fn accept_header() -> String {
+    format!("{}, {};q=0.5", mime::MSGPACK, mime::JSON)
+}
 
-#[derive(Debug, Serialize)]
-struct JoinRequest {
-    name: String,
-    hp: i32,
-    atk: i32,
+/// Content-Type を見て MessagePack / JSON のどちらかで読む
+async fn decode_body<T: DeserializeOwned>(resp: reqwest::Response) -> Result<T, String> {
+    let is_msgpack = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with(mime::MSGPACK));
+    let body = resp
+        .bytes()
+        .await
+        .map_err(|e| net::describe_request_error(&e))?;
+    if is_msgpack {
+        rmp_serde::from_slice(&body).map_err(|e| t!("parse-msgpack", error = e.to_string()))
+    } else {
+        serde_json::from_slice(&body).map_err(|e| t!("parse-json", error = e.to_string()))
+    }
+}
+
+/// HTTP エラーを表示用の1行にまとめる（スキーマ外のボディはそのまま出す）
+fn describe_error(status: reqwest::StatusCode, body: &str) -> String {
+    match serde_json::from_str::<ErrorBody>(body) {
+        Ok(err) => match err.details {
+            Some(details) => format!(
+                "HTTP {} [{}] {} ({})",
+                status.as_u16(),
+                err.code,
+                err.message,
+                details
+            ),
+            None => format!("HTTP {} [{}] {}", status.as_u16(), err.code, err.message),
+        },
+        Err(_) => format!("HTTP {}: {}", status, body),
+    }
 }
 
-#[derive(Debug, Deserialize, Clone)]
-struct JoinResponse {
-    name: String,
-    rank: i32,
-    final_hp: i32,
-    is_winner: bool,
+/// 失敗したレスポンスのボディを読んで describe_error にかける
+async fn error_response(resp: reqwest::Response) -> String {
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    describe_error(status, &body)
 }
 
 #[derive(Debug, Clone)]
@@ -22,43 +113,142 @@ enum ClientEvent {
     Started,
     Completed(JoinResponse),
     Failed(String),
+    Cancelled,
+    CancelFailed(String),
+    /// トークンが無効・期限切れ（ログインし直し）
+    SessionExpired(String),
+    LoggedIn(Session),
+    AuthFailed(String),
+    /// GET /v1/version の結果（問い合わせ先の URL 付き）
+    ServerChecked(String, Result<ServerInfo, String>),
+    /// 一時的な失敗のため待ってから送り直す
+    Retrying(Retrying),
+    /// GET /v1/matches/{id}/log の結果
+    LogLoaded(u64, Result<BattleLog, String>),
+    /// GET /v1/lobbies の結果（問い合わせ先の URL 付き）
+    LobbiesLoaded(String, Result<Vec<LobbyInfo>, String>),
+    /// 疎通確認（URL と往復時間）
+    Pinged(String, Result<(Duration, ServerInfo), String>),
+    /// mDNS で見つけたサーバ
+    #[cfg(feature = "mdns")]
+    Discovered(discovery::DiscoveredServer),
+    #[cfg(feature = "mdns")]
+    DiscoveryFinished(Result<(), String>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Screen {
+    Lobby,
+    Character,
+    History,
+    Arena,
+}
+
+/// 結果待ちの参加（チケット単位で取り消せる）
+struct PendingJoin {
+    ticket: String,
+    server_url: String,
+    cancelling: bool,
+    character: String,         // 履歴に残す参加時のキャラクター
+    request: JoinRequest,      // 送った内容（結果の検証に使う）
+    cancel: CancellationToken, // POST /v1/join の待機を手元で打ち切る
 }
 
 struct AppState {
     server_url: String,
-    player_name: String,
+    profiles: Vec<ServerProfile>,
+    active_profile: Option<String>, // 使っているプロファイルの名前
+    profile_name: String,           // 保存するときの名前
+    pings: HashMap<String, Ping>,   // 正規化した URL ごと
+    #[cfg(feature = "mdns")]
+    discovered: Vec<discovery::DiscoveredServer>,
+    #[cfg(feature = "mdns")]
+    discovering: bool,
+    player_name: String, // ログイン / 登録に使うアカウント名
+    password: String,
+    session: Option<Session>,
+    auth: Option<CancellationToken>, // ログイン・登録の実行中
+
+    net: Net,
+    net_settings: NetSettings, // 編集中の接続設定（Apply で net に反映する）
+
+    // 接続先サーバのバージョン（起動時とログイン時に確認する）
+    server_info: Option<ServerInfo>,
+    checked_url: Option<String>,
+    check: Option<CancellationToken>, // 実行中の GET /v1/version
+    client_too_old: bool,
 
-    hp: i32,
-    atk: i32,
+    screen: Screen,
+    character: Preset, // 今のキャラクター（作成画面で編集する）
+    presets: Vec<Preset>,
+    preset_name: String,
+    squad: Vec<Preset>, // 本人と一緒に参加するキャラクター（名前は squad 内の名前）
+    roster_path: String, // エクスポート先（.json / .toml）
 
     status: String,
-    waiting: bool,
+    pending: Option<PendingJoin>,
     last_result: Option<JoinResponse>,
+    last_request: Option<JoinRequest>, // last_result を返した参加で送った内容
+    verification: Option<Verification>, // last_result を手元で再計算した結果
+    history: Vec<HistoryEntry>,        // 古い順
+    arena: Option<Arena>,              // 直近のマッチの再生
+    log_fetch: Option<(u64, CancellationToken)>, // 取得中のバトルログ（マッチ ID）
+    lobbies: Vec<LobbyInfo>,           // 締め切り前のロビー（GET /v1/lobbies）
+    lobbies_fetch: Option<CancellationToken>,
+    lobbies_fetched_at: Option<Instant>,
+    join_mode: GameMode, // "Join mode" で入る mode
 
-    rx: mpsc::Receiver<ClientEvent>,
-    tx: mpsc::Sender<ClientEvent>,
+    // イベントは発生元のチケットと一緒に届く
+    rx: mpsc::Receiver<(String, ClientEvent)>,
+    tx: mpsc::Sender<(String, ClientEvent)>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
-        use rand::Rng;
-
         let (tx, rx) = mpsc::channel();
-        let mut rng = rand::thread_rng();
-
-        let hp = rng.gen_range(80..120);
-        let atk = rng.gen_range(5..20);
 
         Self {
             server_url: "http://127.0.0.1:3000".to_string(),
+            profiles: Vec::new(),
+            active_profile: None,
+            profile_name: String::new(),
+            pings: HashMap::new(),
+            #[cfg(feature = "mdns")]
+            discovered: Vec::new(),
+            #[cfg(feature = "mdns")]
+            discovering: false,
             player_name: "Shogo_A".to_string(),
+            password: String::new(),
+            session: None,
+            auth: None,
 
-            hp,
-            atk,
+            net: Net::new(NetSettings::default()),
+            net_settings: NetSettings::default(),
 
-            status: "Idle".to_string(),
-            waiting: false,
+            server_info: None,
+            checked_url: None,
+            check: None,
+            client_too_old: false,
+
+            screen: Screen::Lobby,
+            character: Preset::default(),
+            presets: Vec::new(),
+            preset_name: String::new(),
+            squad: Vec::new(),
+            roster_path: "roster.json".to_string(),
+
+            status: t!("status-idle"),
+            pending: None,
             last_result: None,
+            last_request: None,
+            verification: None,
+            history: Vec::new(),
+            arena: None,
+            log_fetch: None,
+            lobbies: Vec::new(),
+            lobbies_fetch: None,
+            lobbies_fetched_at: None,
+            join_mode: GameMode::default(),
             rx,
             tx,
         }
@@ -66,99 +256,635 @@ impl Default for AppState {
 }
 
 impl AppState {
-    fn join(&mut self) {
-        if self.waiting {
-            return;
+    /// 前回保存したログイン状態を復元する
+    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        i18n::install_cjk_font(&cc.egui_ctx);
+        // 初期値の文言も保存した言語で作る
+        if let Some(locale) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Locale>(storage, LOCALE_KEY))
+        {
+            battle_api::i18n::set_locale(locale);
         }
+        let mut app = Self::default();
+        if let Some(storage) = cc.storage {
+            if let Some(url) = eframe::get_value(storage, SERVER_URL_KEY) {
+                app.server_url = url;
+            }
+            if let Some(profiles) = eframe::get_value(storage, PROFILES_KEY) {
+                app.profiles = profiles;
+            }
+            app.active_profile = eframe::get_value(storage, ACTIVE_PROFILE_KEY);
+            if let Some(name) = eframe::get_value(storage, PLAYER_NAME_KEY) {
+                app.player_name = name;
+            }
+            if let Some(path) = eframe::get_value(storage, ROSTER_PATH_KEY) {
+                app.roster_path = path;
+            }
+            if let Some(character) = eframe::get_value::<Preset>(storage, CHARACTER_KEY) {
+                app.preset_name = character.name.clone();
+                app.character = character;
+            }
+            if let Some(presets) = eframe::get_value(storage, PRESETS_KEY) {
+                app.presets = presets;
+            }
+            if let Some(squad) = eframe::get_value(storage, SQUAD_KEY) {
+                app.squad = squad;
+            }
+            if let Some(history) = eframe::get_value(storage, HISTORY_KEY) {
+                app.history = history;
+            }
+            if let Some(settings) = eframe::get_value::<NetSettings>(storage, NET_SETTINGS_KEY) {
+                app.net.apply_settings(settings.clone());
+                app.net_settings = settings;
+            }
+            let session: Option<Session> = eframe::get_value(storage, SESSION_KEY);
+            if let Some(session) = session.filter(|s| !s.is_expired()) {
+                app.player_name = session.name.clone();
+                app.status = t!("status-logged-in", name = session.name.as_str());
+                app.session = Some(session);
+            }
+        }
+        app.migrate_profiles();
+        app.profile_name = app.active_profile.clone().unwrap_or_default();
+        app.check_server();
+        app
+    }
 
-        let mut server_url = self.server_url.trim().trim_end_matches('/').to_string();
-        if server_url.is_empty() {
-            self.status = "Server URL is empty".to_string();
+    /// GET /v1/version でサーバのプロトコルと機能を確認する
+    fn check_server(&mut self) {
+        let Ok(server_url) = self.normalized_server_url() else {
+            return;
+        };
+        if self.checked_url.as_ref() == Some(&server_url) {
             return;
         }
-        if !server_url.starts_with("http://") && !server_url.starts_with("https://") {
-            server_url = format!("http://{server_url}");
+        // URL が変わったら前の確認は打ち切る
+        if let Some(previous) = self.check.take() {
+            previous.cancel();
         }
-        if let Err(e) = reqwest::Url::parse(&server_url) {
-            self.status = format!("Invalid Server URL: {}", e);
+        let cancel = CancellationToken::new();
+        self.check = Some(cancel.clone());
+        self.checked_url = Some(server_url.clone());
+        self.server_info = None;
+        self.client_too_old = false;
+
+        let client = self.net.client();
+        let settings = self.net.settings().clone();
+        let tx = self.tx.clone();
+
+        self.net.spawn(async move {
+            let url = format!("{}{}/version", server_url, API_PREFIX);
+            let resp = net::send_with_retry(
+                || client.get(&url).timeout(settings.request_timeout()),
+                RetryPolicy::Idempotent,
+                settings.max_retries,
+                &cancel,
+                |r| {
+                    let _ = tx.send((String::new(), ClientEvent::Retrying(r)));
+                },
+            )
+            .await;
+            let result = match resp {
+                Ok(r) if r.status().is_success() => r
+                    .json::<ServerInfo>()
+                    .await
+                    .map_err(|e| t!("parse-json", error = e.to_string())),
+                Ok(r) if r.status() == reqwest::StatusCode::NOT_FOUND => {
+                    Err(t!("version-not-reported"))
+                }
+                Ok(r) => Err(error_response(r).await),
+                Err(Failure::Network(msg)) => Err(msg),
+                Err(Failure::Cancelled) => return,
+            };
+            let _ = tx.send((
+                String::new(),
+                ClientEvent::ServerChecked(server_url, result),
+            ));
+        });
+    }
+
+    fn waiting(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    fn normalized_server_url(&self) -> Result<String, String> {
+        normalize_url(&self.server_url)
+    }
+
+    /// lobby を指定しなければ mode の空いているロビーに入る（古いサーバは mode を無視する）
+    fn join(&mut self, mode: GameMode, lobby: Option<u64>) {
+        if self.waiting() {
             return;
         }
 
-        let name = self.player_name.trim().to_string();
-        if name.is_empty() {
-            self.status = "Name is empty".to_string();
+        let server_url = match self.normalized_server_url() {
+            Ok(url) => url,
+            Err(msg) => {
+                self.status = msg;
+                return;
+            }
+        };
+
+        let Some(session) = self.session.clone() else {
+            self.status = t!("status-not-logged-in");
             return;
-        }
+        };
+        let name = session.name;
+        let token = session.token;
 
-        self.waiting = true;
+        let ticket = new_ticket();
+        let cancel = CancellationToken::new();
+
+        // 予算はサーバが確認する（超えていれば invalid_request が返る）
+        let budget = self.stat_budget();
+        self.character.clamp_to(&budget);
+        let (hp, atk) = self.character.base_stats(&budget);
+        let req = JoinRequest {
+            name,
+            hp,
+            atk,
+            ticket: Some(ticket.clone()),
+            class: self.character.class,
+            targeting: self.character.targeting,
+            mode,
+            lobby,
+            squad: self.squad_members(&budget),
+        };
+
+        self.pending = Some(PendingJoin {
+            ticket: ticket.clone(),
+            server_url: server_url.clone(),
+            cancelling: false,
+            character: self.character.summary(&budget),
+            request: req.clone(),
+            cancel: cancel.clone(),
+        });
         self.last_result = None;
-        self.status = "Waiting... (POST /join)".to_string();
-        
-        let hp = self.hp;
-        let atk = self.atk;
+        self.verification = None;
+        self.status = match lobby {
+            Some(id) => t!("status-waiting-lobby", lobby = id),
+            None => t!("status-waiting-mode", mode = mode.as_str()),
+        };
+
+        let client = self.net.client();
+        let settings = self.net.settings().clone();
         let tx = self.tx.clone();
 
-        std::thread::spawn(move || {
-            let _ = tx.send(ClientEvent::Started);
+        self.net.spawn(async move {
+            let send = |ev| {
+                let _ = tx.send((ticket.clone(), ev));
+            };
+            send(ClientEvent::Started);
 
-            // ここはGUIスレッドを止めないために別スレッドで block してOK
-            let client = reqwest::blocking::Client::new();
-            let url = format!("{}/join", server_url);
+            let url = format!("{}{}/join", server_url, API_PREFIX);
+            // 同じチケットで送り直すので、サーバに届いていない失敗だけ再試行する
+            let resp = net::send_with_retry(
+                || {
+                    client
+                        .post(&url)
+                        .timeout(settings.join_timeout())
+                        .bearer_auth(&token)
+                        .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
+                        .header(reqwest::header::ACCEPT, accept_header())
+                        .json(&req)
+                },
+                RetryPolicy::ConnectOnly,
+                settings.max_retries,
+                &cancel,
+                |r| send(ClientEvent::Retrying(r)),
+            )
+            .await;
 
-            let req = JoinRequest {
-                name,
-                hp: hp,
-                atk: atk,
+            let ev = match resp {
+                Ok(r) if r.status() == reqwest::StatusCode::UNAUTHORIZED => {
+                    ClientEvent::SessionExpired(error_response(r).await)
+                }
+                Ok(r) if !r.status().is_success() => ClientEvent::Failed(error_response(r).await),
+                Ok(r) => match decode_body::<JoinResponse>(r).await {
+                    Ok(data) => ClientEvent::Completed(data),
+                    Err(e) => ClientEvent::Failed(e),
+                },
+                Err(Failure::Network(msg)) => ClientEvent::Failed(msg),
+                // 手元で待機をやめた（サーバは切断として扱う）
+                Err(Failure::Cancelled) => ClientEvent::Cancelled,
             };
+            send(ev);
+        });
+    }
 
-            let resp = client.post(url).json(&req).send();
+    /// DELETE /v1/tickets/{id} で待機中の参加を取り消す
+    fn cancel(&mut self) {
+        let Some(pending) = self.pending.as_mut() else {
+            return;
+        };
+        if pending.cancelling {
+            return;
+        }
+        pending.cancelling = true;
+        self.status = t!("status-cancelling");
 
-            match resp {
-                Ok(r) => {
-                    if !r.status().is_success() {
-                        let status = r.status();
-                        let body = r.text().unwrap_or_default();
-                        let _ = tx.send(ClientEvent::Failed(format!("HTTP {}: {}", status, body)));
-                        return;
-                    }
+        let ticket = pending.ticket.clone();
+        let join = pending.cancel.clone();
+        let url = format!("{}{}/tickets/{}", pending.server_url, API_PREFIX, ticket);
+        let token = self
+            .session
+            .as_ref()
+            .map(|s| s.token.clone())
+            .unwrap_or_default();
+        let client = self.net.client();
+        let settings = self.net.settings().clone();
+        let tx = self.tx.clone();
 
-                    match r.json::<JoinResponse>() {
-                        Ok(data) => {
-                            let _ = tx.send(ClientEvent::Completed(data));
-                        }
-                        Err(e) => {
-                            let _ =
-                                tx.send(ClientEvent::Failed(format!("JSON parse error: {}", e)));
-                        }
-                    }
+        self.net.spawn(async move {
+            let resp = net::send_with_retry(
+                || {
+                    client
+                        .delete(&url)
+                        .timeout(settings.request_timeout())
+                        .bearer_auth(&token)
+                        .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
+                },
+                RetryPolicy::ConnectOnly,
+                settings.max_retries,
+                &CancellationToken::new(),
+                |r| {
+                    let _ = tx.send((ticket.clone(), ClientEvent::Retrying(r)));
+                },
+            )
+            .await;
+            let ev = match resp {
+                Ok(r) if r.status().is_success() => {
+                    // サーバからの join_cancelled を待たずに手元の待機も終える
+                    join.cancel();
+                    ClientEvent::Cancelled
                 }
-                Err(e) => {
-                    let _ = tx.send(ClientEvent::Failed(format!("Request error: {}", e)));
+                // バトルが始まっていれば結果はそのまま届く
+                Ok(r) => ClientEvent::CancelFailed(error_response(r).await),
+                Err(Failure::Network(msg)) => {
+                    // サーバに届かないなら結果も届かないので、手元で待機をやめる
+                    join.cancel();
+                    ClientEvent::CancelFailed(msg)
                 }
+                Err(Failure::Cancelled) => return,
+            };
+            let _ = tx.send((ticket, ev));
+        });
+    }
+
+    /// POST /v1/register または POST /v1/login（endpoint で切り替え）
+    fn authenticate(&mut self, endpoint: &'static str) {
+        if self.auth.is_some() {
+            return;
+        }
+
+        let server_url = match self.normalized_server_url() {
+            Ok(url) => url,
+            Err(msg) => {
+                self.status = msg;
+                return;
             }
+        };
+
+        let req = CredentialsRequest {
+            name: self.player_name.trim().to_string(),
+            password: std::mem::take(&mut self.password),
+        };
+        if req.name.is_empty() {
+            self.status = t!("status-name-empty");
+            return;
+        }
+
+        // URL が変わっていたら確認し直す（結果は非同期に届く）
+        self.check_server();
+
+        let cancel = CancellationToken::new();
+        self.auth = Some(cancel.clone());
+        self.status = t!("status-waiting-auth", endpoint = endpoint);
+
+        // 登録は送り直すと 409 になり得るので、届いていない失敗だけ再試行する
+        let policy = if endpoint == "login" {
+            RetryPolicy::Idempotent
+        } else {
+            RetryPolicy::ConnectOnly
+        };
+        let client = self.net.client();
+        let settings = self.net.settings().clone();
+        let tx = self.tx.clone();
+
+        self.net.spawn(async move {
+            let url = format!("{}{}/{}", server_url, API_PREFIX, endpoint);
+            let resp = net::send_with_retry(
+                || {
+                    client
+                        .post(&url)
+                        .timeout(settings.request_timeout())
+                        .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
+                        .json(&req)
+                },
+                policy,
+                settings.max_retries,
+                &cancel,
+                |r| {
+                    let _ = tx.send((String::new(), ClientEvent::Retrying(r)));
+                },
+            )
+            .await;
+
+            let ev = match resp {
+                Ok(r) if r.status().is_success() => match r.json::<Session>().await {
+                    Ok(session) => ClientEvent::LoggedIn(session),
+                    Err(e) => ClientEvent::AuthFailed(t!("parse-json", error = e.to_string())),
+                },
+                Ok(r) => ClientEvent::AuthFailed(error_response(r).await),
+                Err(Failure::Network(msg)) => ClientEvent::AuthFailed(msg),
+                Err(Failure::Cancelled) => ClientEvent::AuthFailed(t!("status-cancelled")),
+            };
+            // 認証イベントはチケットに紐付かない
+            let _ = tx.send((String::new(), ev));
+        });
+    }
+
+    /// 実行中のログイン・登録をやめる
+    fn stop_auth(&mut self) {
+        if let Some(cancel) = self.auth.take() {
+            cancel.cancel();
+            self.status = t!("status-cancelled");
+        }
+    }
+
+    /// GET /v1/matches/{id}/log を取ってアリーナで再生する（古いサーバでは何もしない）
+    fn fetch_battle_log(&mut self, server_url: String, match_id: u64) {
+        let supported = self
+            .server_info
+            .as_ref()
+            .is_some_and(|info| info.supports(capability::BATTLE_LOG));
+        if !supported {
+            return;
+        }
+        if let Some((_, previous)) = self.log_fetch.take() {
+            previous.cancel();
+        }
+        let cancel = CancellationToken::new();
+        self.log_fetch = Some((match_id, cancel.clone()));
+
+        let client = self.net.client();
+        let settings = self.net.settings().clone();
+        let tx = self.tx.clone();
+
+        self.net.spawn(async move {
+            let url = format!("{}{}/matches/{}/log", server_url, API_PREFIX, match_id);
+            let resp = net::send_with_retry(
+                || {
+                    client
+                        .get(&url)
+                        .header(reqwest::header::ACCEPT, accept_header())
+                        .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
+                        .timeout(settings.request_timeout())
+                },
+                RetryPolicy::Idempotent,
+                settings.max_retries,
+                &cancel,
+                |r| {
+                    let _ = tx.send((String::new(), ClientEvent::Retrying(r)));
+                },
+            )
+            .await;
+            let result = match resp {
+                Ok(r) if r.status().is_success() => decode_body::<BattleLog>(r).await,
+                Ok(r) => Err(error_response(r).await),
+                Err(Failure::Network(msg)) => Err(msg),
+                Err(Failure::Cancelled) => return,
+            };
+            let _ = tx.send((String::new(), ClientEvent::LogLoaded(match_id, result)));
         });
     }
 
+    fn logout(&mut self) {
+        if self.waiting() {
+            return;
+        }
+        self.session = None;
+        self.sync_profile_session();
+        self.last_result = None;
+        self.verification = None;
+        self.arena = None;
+        if let Some((_, fetch)) = self.log_fetch.take() {
+            fetch.cancel();
+        }
+        self.clear_lobbies();
+        self.status = t!("status-logged-out");
+    }
+
     fn pump_events(&mut self) {
         // まとめて捌く（描画ごとに詰まりにくい）
-        while let Ok(ev) = self.rx.try_recv() {
+        while let Ok((ticket, ev)) = self.rx.try_recv() {
+            match ev {
+                ClientEvent::LoggedIn(session) => {
+                    if self.auth.take().is_none() {
+                        // 取り消した後に届いた
+                        continue;
+                    }
+                    self.status = t!("status-logged-in", name = session.name.as_str());
+                    self.session = Some(session);
+                    self.sync_profile_session();
+                    continue;
+                }
+                ClientEvent::AuthFailed(msg) => {
+                    if self.auth.take().is_some() {
+                        self.status = t!("status-error", message = msg);
+                    }
+                    continue;
+                }
+                ClientEvent::ServerChecked(url, result) => {
+                    // 確認中に URL が変わっていたら古い結果は捨てる
+                    if self.checked_url.as_ref() == Some(&url) {
+                        self.check = None;
+                        self.apply_server_info(result);
+                    }
+                    continue;
+                }
+                ClientEvent::LogLoaded(id, result) => {
+                    if self.log_fetch.as_ref().map(|(i, _)| *i) != Some(id) {
+                        continue;
+                    }
+                    self.log_fetch = None;
+                    match result {
+                        Ok(log) => {
+                            self.verify_result(&log);
+                            // squad のキャラクターも自分のものとして強調する
+                            let own = match &self.last_result {
+                                Some(r) if r.match_id == Some(id) => squad::own_names(r),
+                                _ => self.session.iter().map(|s| s.name.clone()).collect(),
+                            };
+                            self.arena = Some(Arena::new(log, &own));
+                            // 結果を見ていたらそのまま再生を始める
+                            if self.screen == Screen::Lobby {
+                                self.screen = Screen::Arena;
+                            }
+                        }
+                        Err(msg) => {
+                            self.mark_unverifiable(id);
+                            self.status = t!("status-log-unavailable", message = msg);
+                        }
+                    }
+                    continue;
+                }
+                ClientEvent::LobbiesLoaded(url, result) => {
+                    self.apply_lobbies(url, result);
+                    continue;
+                }
+                ClientEvent::Pinged(url, result) => {
+                    self.apply_ping(url, result);
+                    continue;
+                }
+                #[cfg(feature = "mdns")]
+                ClientEvent::Discovered(server) => {
+                    self.add_discovered(server);
+                    continue;
+                }
+                #[cfg(feature = "mdns")]
+                ClientEvent::DiscoveryFinished(result) => {
+                    self.discovering = false;
+                    self.status = match result {
+                        Ok(()) if self.discovered.is_empty() => t!("status-no-servers-found"),
+                        Ok(()) => t!("status-servers-found", count = self.discovered.len()),
+                        Err(msg) => t!("status-discovery-failed", message = msg),
+                    };
+                    continue;
+                }
+                ClientEvent::Retrying(r) if ticket.is_empty() => {
+                    self.status = retry_status(&r);
+                    continue;
+                }
+                _ => {}
+            }
+
+            // 取り消し済みの参加から遅れて届いたイベントは捨てる
+            if self.pending.as_ref().map(|p| &p.ticket) != Some(&ticket) {
+                continue;
+            }
+
             match ev {
                 ClientEvent::Started => {
                     // 表示更新だけ
-                    self.status = "Waiting... (server is matching / battling)".to_string();
+                    self.status = t!("status-matching");
                 }
                 ClientEvent::Completed(res) => {
-                    self.waiting = false;
-                    self.status = "Done".to_string();
+                    if let Some(pending) = self.pending.take() {
+                        if let Some(id) = res.match_id {
+                            self.fetch_battle_log(pending.server_url.clone(), id);
+                        }
+                        self.last_request = Some(pending.request.clone());
+                        self.record_result(&res, pending);
+                    }
+                    self.status = t!("status-done");
                     self.last_result = Some(res);
+                    self.verification = None;
                 }
                 ClientEvent::Failed(msg) => {
-                    self.waiting = false;
-                    self.status = format!("Error: {}", msg);
+                    // 取り消し中なら DELETE の応答より先に join_cancelled が届くことがある
+                    let cancelling = self.pending.take().is_some_and(|p| p.cancelling);
+                    self.status = if cancelling {
+                        t!("status-cancelled")
+                    } else {
+                        t!("status-error", message = msg)
+                    };
+                }
+                ClientEvent::Cancelled => {
+                    // DELETE が通った、または手元で待機をやめた
+                    self.pending = None;
+                    self.status = t!("status-cancelled");
+                }
+                ClientEvent::CancelFailed(msg) => {
+                    // バトルが始まっていれば結果はそのまま届く
+                    if let Some(p) = self.pending.as_mut() {
+                        p.cancelling = false;
+                    }
+                    self.status = t!("status-cancel-failed", message = msg);
+                }
+                ClientEvent::SessionExpired(msg) => {
+                    self.pending = None;
+                    self.session = None;
+                    self.sync_profile_session();
+                    self.status = t!("status-session-expired", message = msg);
+                }
+                ClientEvent::Retrying(r) => {
+                    self.status = retry_status(&r);
+                }
+                ClientEvent::LoggedIn(_)
+                | ClientEvent::AuthFailed(_)
+                | ClientEvent::ServerChecked(..)
+                | ClientEvent::LogLoaded(..)
+                | ClientEvent::LobbiesLoaded(..)
+                | ClientEvent::Pinged(..) => {}
+                #[cfg(feature = "mdns")]
+                ClientEvent::Discovered(_) | ClientEvent::DiscoveryFinished(_) => {}
+            }
+        }
+    }
+}
+
+impl AppState {
+    fn apply_server_info(&mut self, result: Result<ServerInfo, String>) {
+        match result {
+            Ok(info) => {
+                if info.min_protocol_version > PROTOCOL_VERSION {
+                    self.client_too_old = true;
+                    self.status = t!(
+                        "status-client-too-old",
+                        client = PROTOCOL_VERSION,
+                        min = info.min_protocol_version
+                    );
                 }
+                self.server_info = Some(info);
             }
+            // 確認できなくても操作は止めない（実際のリクエストでエラーになる）
+            Err(msg) => self.status = t!("status-version-check-failed", message = msg),
         }
     }
+
+    /// 今のキャラクターをロスターファイルに書き出す（形式は拡張子で決める）
+    fn export_roster(&mut self) {
+        let name = self
+            .session
+            .as_ref()
+            .map(|s| s.name.clone())
+            .unwrap_or_else(|| self.player_name.trim().to_string());
+        let path = std::path::Path::new(self.roster_path.trim());
+        let text = match RosterFormat::from_path(path) {
+            Some(format) => {
+                let roster =
+                    squad::roster_file(&name, &self.character, &self.squad, &self.stat_budget());
+                // サーバが読めないファイルは書き出さない（名前の長さ・重複など）
+                roster
+                    .validate()
+                    .and_then(|()| roster.to_string(format))
+                    .map_err(|e| e.to_string())
+            }
+            None => Err(t!("roster-bad-extension")),
+        };
+
+        self.status = match text.and_then(|t| std::fs::write(path, t).map_err(|e| e.to_string())) {
+            Ok(()) => t!("status-roster-exported", path = path.display().to_string()),
+            Err(e) => t!("status-export-failed", message = e),
+        };
+    }
+}
+
+fn retry_status(r: &Retrying) -> String {
+    t!(
+        "status-retrying",
+        reason = r.reason.as_str(),
+        delay = format!("{:.1}", r.delay.as_secs_f32()),
+        attempt = r.attempt,
+        max = r.max_retries
+    )
+}
+
+fn new_ticket() -> String {
+    use rand::Rng;
+    format!("{:032x}", rand::thread_rng().gen::<u128>())
 }
 
 impl eframe::App for AppState {
@@ -166,66 +892,295 @@ impl eframe::App for AppState {
         self.pump_events();
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Battle Client");
+            ui.horizontal(|ui| {
+                ui.heading("Battle Client");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    i18n::ui_language(ui);
+                });
+            });
             ui.add_space(8.0);
 
             ui.horizontal(|ui| {
-                ui.label("Server URL:");
-                ui.text_edit_singleline(&mut self.server_url);
+                ui.label(t!("label-server-url"));
+                ui.add_enabled(
+                    self.session.is_none(),
+                    egui::TextEdit::singleline(&mut self.server_url),
+                );
+                if ui.small_button(t!("button-check")).clicked() {
+                    let url = self.server_url.clone();
+                    self.ping(&url);
+                }
+                if let Some(ping) = self.current_ping() {
+                    ui.small(ping);
+                }
+            });
+
+            egui::CollapsingHeader::new(t!("header-servers")).show(ui, |ui| {
+                self.ui_servers(ui);
+            });
+
+            egui::CollapsingHeader::new(t!("header-connection-settings")).show(ui, |ui| {
+                self.ui_net_settings(ui);
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(t!("label-server"));
+                match &self.server_info {
+                    Some(info) => ui.monospace(t!(
+                        "server-version",
+                        version = info.server_version.as_str(),
+                        protocol = info.protocol_version,
+                        client = PROTOCOL_VERSION
+                    )),
+                    None => ui.monospace(t!("server-unknown")),
+                };
             });
 
             ui.horizontal(|ui| {
-                ui.label("Name:");
-                ui.text_edit_singleline(&mut self.player_name);
+                ui.selectable_value(&mut self.screen, Screen::Lobby, t!("tab-lobby"));
+                ui.selectable_value(&mut self.screen, Screen::Character, t!("tab-character"));
+                ui.selectable_value(&mut self.screen, Screen::History, t!("tab-history"));
+                ui.selectable_value(&mut self.screen, Screen::Arena, t!("tab-arena"));
             });
-            
             ui.separator();
-            ui.label("Character Status:");
-            ui.monospace(format!("HP  : {}", self.hp));
-            ui.monospace(format!("ATK : {}", self.atk));
 
-            ui.add_space(8.0);
+            match self.screen {
+                Screen::Character => self.ui_character(ui),
+                Screen::History => self.ui_history(ui),
+                Screen::Arena => self.ui_arena(ui),
+                Screen::Lobby if self.session.is_none() => self.ui_login(ui),
+                Screen::Lobby => self.ui_lobby(ui),
+            }
+        });
 
-            let join_btn = ui.add_enabled(!self.waiting, egui::Button::new("Join"));
-            if join_btn.clicked() {
-                self.join();
+        // 待機中はそれなりに再描画（CPUを焼かない程度）
+        if self.waiting()
+            || self.auth.is_some()
+            || self.check.is_some()
+            || self.log_fetch.is_some()
+            || self.probing()
+        {
+            ctx.request_repaint_after(std::time::Duration::from_millis(50));
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SERVER_URL_KEY, &self.server_url);
+        eframe::set_value(storage, PROFILES_KEY, &self.profiles);
+        eframe::set_value(storage, ACTIVE_PROFILE_KEY, &self.active_profile);
+        eframe::set_value(storage, PLAYER_NAME_KEY, &self.player_name);
+        eframe::set_value(storage, ROSTER_PATH_KEY, &self.roster_path);
+        eframe::set_value(storage, SESSION_KEY, &self.session);
+        eframe::set_value(storage, CHARACTER_KEY, &self.character);
+        eframe::set_value(storage, PRESETS_KEY, &self.presets);
+        eframe::set_value(storage, SQUAD_KEY, &self.squad);
+        eframe::set_value(storage, HISTORY_KEY, &self.history);
+        eframe::set_value(storage, NET_SETTINGS_KEY, self.net.settings());
+        eframe::set_value(storage, LOCALE_KEY, &battle_api::i18n::locale());
+    }
+}
+
+impl AppState {
+    fn ui_net_settings(&mut self, ui: &mut egui::Ui) {
+        let s = &mut self.net_settings;
+        egui::Grid::new("net_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(t!("label-connect-timeout"));
+                ui.add(
+                    egui::DragValue::new(&mut s.connect_timeout_secs)
+                        .range(1..=60)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+                ui.label(t!("label-request-timeout"));
+                ui.add(
+                    egui::DragValue::new(&mut s.request_timeout_secs)
+                        .range(1..=300)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+                ui.label(t!("label-join-timeout"));
+                ui.add(
+                    egui::DragValue::new(&mut s.join_timeout_secs)
+                        .range(5..=3600)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+                ui.label(t!("label-max-retries"));
+                ui.add(egui::DragValue::new(&mut s.max_retries).range(0..=10));
+                ui.end_row();
+            });
+        ui.horizontal(|ui| {
+            let changed = self.net_settings != *self.net.settings();
+            if ui
+                .add_enabled(changed, egui::Button::new(t!("button-apply")))
+                .clicked()
+            {
+                // 実行中のリクエストは前の設定のまま
+                self.net.apply_settings(self.net_settings.clone());
+                self.status = t!("status-settings-applied");
             }
+            if ui.button(t!("button-defaults")).clicked() {
+                self.net_settings = NetSettings::default();
+            }
+        });
+    }
 
-            ui.add_space(12.0);
-            ui.label(format!("Status: {}", self.status));
+    fn ui_login(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(t!("label-name"));
+            ui.text_edit_singleline(&mut self.player_name);
+        });
+        ui.horizontal(|ui| {
+            ui.label(t!("label-password"));
+            ui.add(egui::TextEdit::singleline(&mut self.password).password(true));
+        });
 
-            ui.add_space(12.0);
-            ui.separator();
-            ui.label("Result:");
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            let idle = self.auth.is_none() && !self.client_too_old;
+            if ui
+                .add_enabled(idle, egui::Button::new(t!("button-login")))
+                .clicked()
+            {
+                self.authenticate("login");
+            }
+            if ui
+                .add_enabled(idle, egui::Button::new(t!("button-register")))
+                .clicked()
+            {
+                self.authenticate("register");
+            }
+            if ui
+                .add_enabled(self.auth.is_some(), egui::Button::new(t!("button-stop")))
+                .clicked()
+            {
+                self.stop_auth();
+            }
+        });
 
-            if let Some(r) = &self.last_result {
-                ui.monospace(format!("name      : {}", r.name));
-                ui.monospace(format!("rank      : {}", r.rank));
-                ui.monospace(format!("final_hp  : {}", r.final_hp));
-                ui.monospace(format!("is_winner : {}", r.is_winner));
-            } else {
-                ui.monospace("(no result)");
+        ui.add_space(12.0);
+        ui.label(t!("label-status", status = self.status.as_str()));
+    }
+
+    fn ui_lobby(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let name = self.session.as_ref().map(|s| s.name.as_str()).unwrap_or("");
+            ui.label(t!("label-logged-in-as", name = name));
+            if ui
+                .add_enabled(!self.waiting(), egui::Button::new(t!("button-logout")))
+                .clicked()
+            {
+                self.logout();
             }
+        });
 
-            ui.add_space(8.0);
-            ui.small("Note: /join はレスポンスが返るまで待機します（10秒待機 + バトル時間）。");
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(t!("label-character"));
+            ui.monospace(self.character.summary(&self.stat_budget()));
+            if ui.small_button(t!("button-edit")).clicked() {
+                self.screen = Screen::Character;
+            }
         });
+        self.ui_squad_summary(ui);
 
-        // 待機中はそれなりに再描画（CPUを焼かない程度）
-        if self.waiting {
-            ctx.request_repaint_after(std::time::Duration::from_millis(50));
+        ui.horizontal(|ui| {
+            ui.label(t!("label-roster-file"));
+            ui.text_edit_singleline(&mut self.roster_path);
+            if ui.button(t!("button-export")).clicked() {
+                self.export_roster();
+            }
+        });
+
+        ui.add_space(8.0);
+
+        egui::CollapsingHeader::new(t!("header-lobbies"))
+            .default_open(true)
+            .show(ui, |ui| {
+                self.ui_lobby_browser(ui);
+            });
+
+        ui.horizontal(|ui| {
+            // mode・ロビーを指定しない（classic のロビーに入る）
+            let join_btn = ui
+                .add_enabled(self.can_join(), egui::Button::new(t!("button-quick-join")))
+                .on_hover_text(t!("hover-quick-join"));
+            if join_btn.clicked() {
+                self.join(GameMode::Classic, None);
+            }
+
+            let cancellable = self.pending.as_ref().is_some_and(|p| !p.cancelling);
+            let cancel_btn = ui.add_enabled(cancellable, egui::Button::new(t!("button-cancel")));
+            if cancel_btn.clicked() {
+                self.cancel();
+            }
+        });
+
+        ui.add_space(12.0);
+        ui.label(t!("label-status", status = self.status.as_str()));
+
+        ui.add_space(12.0);
+        ui.separator();
+        ui.label(t!("label-result"));
+
+        if let Some(r) = &self.last_result {
+            if r.squad.is_empty() {
+                ui.monospace(t!("result-name", name = r.name.as_str()));
+                ui.monospace(t!("result-rank", rank = r.rank));
+                ui.monospace(t!("result-final-hp", final_hp = r.final_hp));
+                ui.monospace(t!("result-is-winner", is_winner = r.is_winner.to_string()));
+            } else {
+                squad::ui_results(ui, r);
+            }
+            self.ui_verification(ui);
+            if self.arena.is_some() && ui.button(t!("button-watch-replay")).clicked() {
+                self.screen = Screen::Arena;
+            }
+        } else {
+            ui.monospace(t!("result-none"));
         }
+
+        ui.add_space(8.0);
+        ui.small(t!("note-join-wait"));
     }
 }
 
 fn main() -> eframe::Result<()> {
+    // 保存した言語は AppState::new で読み直す
+    i18n::init(Locale::from_env());
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([520.0, 350.0]),
+        viewport: egui::ViewportBuilder::default().with_inner_size([680.0, 720.0]),
         ..Default::default()
     };
     eframe::run_native(
         "Battle Client",
         options,
-        Box::new(|_cc| Ok(Box::new(AppState::default()))),
+        Box::new(|cc| Ok(Box::new(AppState::new(cc)))),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_url_adds_a_scheme_and_trims() {
+        let cases = [
+            ("127.0.0.1:3000", "http://127.0.0.1:3000"),
+            ("  http://localhost:3000/ ", "http://localhost:3000"),
+            ("https://battle.example//", "https://battle.example"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalize_url(input).as_deref(), Ok(expected), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn normalize_url_rejects_empty_and_malformed_urls() {
+        for input in ["", "   ", "/", "http://exa mple", "battle.example:port"] {
+            assert!(normalize_url(input).is_err(), "{:?}", input);
+        }
+    }
+}
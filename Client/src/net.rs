@@ -0,0 +1,297 @@
+use battle_api::t;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+// ===== 通信（tokio ランタイム + 共有の reqwest::Client） =====
+//
+// GUI スレッドは止めずにランタイムへタスクを投げ、結果は ClientEvent で受け取る。
+// 一時的な失敗（接続できない・429・502/503/504）はバックオフを挟んで再試行する。
+
+/// 接続設定（接続画面で変更でき、eframe のストレージに保存する）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetSettings {
+    pub connect_timeout_secs: u64,
+    /// /v1/version, /v1/login などの短いリクエスト
+    pub request_timeout_secs: u64,
+    /// /v1/join はマッチが終わるまで返らないので長めにする
+    pub join_timeout_secs: u64,
+    /// 一時的な失敗を何回まで再試行するか（0 で再試行しない）
+    pub max_retries: u32,
+}
+
+impl Default for NetSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 5,
+            request_timeout_secs: 10,
+            join_timeout_secs: 120,
+            max_retries: 3,
+        }
+    }
+}
+
+impl NetSettings {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs.max(1))
+    }
+
+    pub fn join_timeout(&self) -> Duration {
+        Duration::from_secs(self.join_timeout_secs.max(1))
+    }
+}
+
+pub struct Net {
+    runtime: tokio::runtime::Runtime,
+    client: reqwest::Client,
+    settings: NetSettings,
+}
+
+impl Net {
+    pub fn new(settings: NetSettings) -> Self {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("battle-client-net")
+            .enable_all()
+            .build()
+            .expect("failed to start tokio runtime");
+        Self {
+            runtime,
+            client: build_client(&settings),
+            settings,
+        }
+    }
+
+    pub fn settings(&self) -> &NetSettings {
+        &self.settings
+    }
+
+    /// 以降のリクエストから新しい設定を使う（実行中のものはそのまま）
+    pub fn apply_settings(&mut self, settings: NetSettings) {
+        if settings != self.settings {
+            self.client = build_client(&settings);
+            self.settings = settings;
+        }
+    }
+
+    /// 中身は Arc なので clone してタスクに渡す
+    pub fn client(&self) -> reqwest::Client {
+        self.client.clone()
+    }
+
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.runtime.spawn(task);
+    }
+}
+
+fn build_client(settings: &NetSettings) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(settings.connect_timeout_secs.max(1)))
+        .build()
+        .expect("failed to build HTTP client")
+}
+
+// ===== 再試行 =====
+
+/// どこまで再試行してよいか
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryPolicy {
+    /// 何度送っても同じ結果になるもの（GET /v1/version, POST /v1/login）
+    Idempotent,
+    /// サーバに届いていないと分かる失敗（接続失敗・429）だけ再試行する（/v1/join など）
+    ConnectOnly,
+}
+
+/// 再試行の前に UI へ知らせる内容
+#[derive(Clone, Debug)]
+pub struct Retrying {
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub delay: Duration,
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub enum Failure {
+    /// CancellationToken で取り消された
+    Cancelled,
+    /// 接続できない・タイムアウトなど（再試行し尽くした後）
+    Network(String),
+}
+
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(8);
+
+/// 送信して、一時的な失敗ならバックオフして送り直す
+///
+/// 成功以外のステータスもそのまま返す（再試行し尽くした 503 なども）。
+pub async fn send_with_retry<B, R>(
+    build: B,
+    policy: RetryPolicy,
+    max_retries: u32,
+    cancel: &CancellationToken,
+    mut on_retry: R,
+) -> Result<reqwest::Response, Failure>
+where
+    B: Fn() -> reqwest::RequestBuilder,
+    R: FnMut(Retrying),
+{
+    let mut attempt = 0;
+    loop {
+        let result = tokio::select! {
+            _ = cancel.cancelled() => return Err(Failure::Cancelled),
+            result = build().send() => result,
+        };
+
+        let retries_left = attempt < max_retries;
+        let (reason, retry_after) = match result {
+            Ok(resp) => match transient_status(resp.status(), resp.headers(), policy) {
+                Some(retry_after) if retries_left => {
+                    (format!("HTTP {}", resp.status()), retry_after)
+                }
+                _ => return Ok(resp),
+            },
+            Err(e) if retries_left && retryable_error(&e, policy) => {
+                (describe_request_error(&e), None)
+            }
+            Err(e) => return Err(Failure::Network(describe_request_error(&e))),
+        };
+        attempt += 1;
+
+        let delay = retry_after.unwrap_or_else(|| backoff(attempt));
+        on_retry(Retrying {
+            attempt,
+            max_retries,
+            delay,
+            reason,
+        });
+        tokio::select! {
+            _ = cancel.cancelled() => return Err(Failure::Cancelled),
+            _ = tokio::time::sleep(delay) => {}
+        }
+    }
+}
+
+/// 送り直すべきステータスなら Some(Retry-After)
+fn transient_status(
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    policy: RetryPolicy,
+) -> Option<Option<Duration>> {
+    let retry = match status.as_u16() {
+        // 回数制限・ロビー満員はリクエストが処理されていない
+        429 => true,
+        502..=504 => policy == RetryPolicy::Idempotent,
+        _ => false,
+    };
+    retry.then(|| {
+        headers
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(|secs| Duration::from_secs(secs).min(BACKOFF_MAX * 4))
+    })
+}
+
+fn retryable_error(e: &reqwest::Error, policy: RetryPolicy) -> bool {
+    match policy {
+        RetryPolicy::Idempotent => e.is_connect() || e.is_timeout(),
+        RetryPolicy::ConnectOnly => e.is_connect(),
+    }
+}
+
+/// 指数バックオフ（上限あり）に 50〜100% のゆらぎを入れる
+fn backoff(attempt: u32) -> Duration {
+    use rand::Rng;
+    let exp = BACKOFF_BASE.saturating_mul(1 << attempt.min(5).saturating_sub(1));
+    let capped = exp.min(BACKOFF_MAX);
+    capped.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+pub fn describe_request_error(e: &reqwest::Error) -> String {
+    if e.is_timeout() {
+        t!("net-timeout")
+    } else if e.is_connect() {
+        t!("net-connect", error = e.to_string())
+    } else {
+        t!("net-request", error = e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use reqwest::StatusCode;
+
+    fn retry_after(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_with_jitter() {
+        for attempt in 1..=10 {
+            let exp = BACKOFF_BASE * 2u32.pow(attempt.min(5) - 1);
+            let full = exp.min(BACKOFF_MAX);
+            for _ in 0..20 {
+                let delay = backoff(attempt);
+                assert!(
+                    delay >= full / 2 && delay <= full,
+                    "attempt {}: {:?}",
+                    attempt,
+                    delay
+                );
+            }
+        }
+        assert!(backoff(100) <= BACKOFF_MAX);
+    }
+
+    #[test]
+    fn only_unprocessed_statuses_are_retried() {
+        let none = HeaderMap::new();
+        let check = |status: u16, policy| {
+            transient_status(StatusCode::from_u16(status).unwrap(), &none, policy).is_some()
+        };
+        // 429 は処理されていないのでどちらでも送り直す
+        assert!(check(429, RetryPolicy::Idempotent));
+        assert!(check(429, RetryPolicy::ConnectOnly));
+        // 502〜504 は届いたかもしれないので冪等なリクエストだけ
+        for status in [502, 503, 504] {
+            assert!(check(status, RetryPolicy::Idempotent));
+            assert!(!check(status, RetryPolicy::ConnectOnly));
+        }
+        for status in [200, 400, 401, 409, 500] {
+            assert!(!check(status, RetryPolicy::Idempotent), "{}", status);
+        }
+    }
+
+    #[test]
+    fn retry_after_is_read_and_capped() {
+        let status = StatusCode::TOO_MANY_REQUESTS;
+        let policy = RetryPolicy::Idempotent;
+        assert_eq!(
+            transient_status(status, &retry_after(" 3 "), policy),
+            Some(Some(Duration::from_secs(3)))
+        );
+        assert_eq!(
+            transient_status(status, &retry_after("86400"), policy),
+            Some(Some(BACKOFF_MAX * 4))
+        );
+        // HTTP 日付の形式は読まずにバックオフに任せる
+        assert_eq!(
+            transient_status(
+                status,
+                &retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+                policy
+            ),
+            Some(None)
+        );
+    }
+}
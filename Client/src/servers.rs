@@ -0,0 +1,320 @@
+use crate::net;
+use crate::{normalize_url, AppState, ClientEvent, Session};
+use battle_api::{t, ServerInfo, API_PREFIX};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+// ===== サーバのプロファイル =====
+//
+// 名前・URL・そのサーバで発行されたトークンを組にして保存し、切り替えて使う。
+// 疎通確認は GET /v1/version を1回だけ送って往復時間を出す（再試行はしない）。
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServerProfile {
+    pub name: String,
+    pub url: String,
+    /// このサーバでログインしたときのトークン（ログアウト・期限切れで消す）
+    #[serde(default)]
+    pub session: Option<Session>,
+}
+
+/// 疎通確認の結果（URL ごと）
+pub enum Ping {
+    Pending,
+    Ok { latency: Duration, version: String },
+    Failed(String),
+}
+
+impl Ping {
+    fn label(&self) -> String {
+        match self {
+            Ping::Pending => t!("ping-pending"),
+            Ping::Ok { latency, version } => t!(
+                "ping-ok",
+                ms = latency.as_millis().to_string(),
+                version = version.as_str()
+            ),
+            Ping::Failed(msg) => t!("ping-failed", message = msg.as_str()),
+        }
+    }
+}
+
+impl AppState {
+    /// 前回のバージョンで保存した URL とトークンを最初のプロファイルにする
+    pub(crate) fn migrate_profiles(&mut self) {
+        if !self.profiles.is_empty() {
+            return;
+        }
+        self.profiles.push(ServerProfile {
+            name: "default".to_string(),
+            url: self.server_url.clone(),
+            session: self.session.clone(),
+        });
+        self.active_profile = Some("default".to_string());
+    }
+
+    fn active_profile_mut(&mut self) -> Option<&mut ServerProfile> {
+        let name = self.active_profile.as_ref()?;
+        self.profiles.iter_mut().find(|p| &p.name == name)
+    }
+
+    /// ログイン・ログアウトの後に、今の URL と同じプロファイルへトークンを写す
+    pub(crate) fn sync_profile_session(&mut self) {
+        let url = normalize_url(&self.server_url).ok();
+        let session = self.session.clone();
+        if let Some(profile) = self.active_profile_mut() {
+            if normalize_url(&profile.url).ok() == url {
+                profile.session = session;
+            }
+        }
+    }
+
+    fn can_switch(&self) -> bool {
+        !self.waiting() && self.auth.is_none()
+    }
+
+    pub(crate) fn use_profile(&mut self, index: usize) {
+        if !self.can_switch() {
+            return;
+        }
+        let Some(profile) = self.profiles.get(index).cloned() else {
+            return;
+        };
+        self.server_url = profile.url;
+        self.session = profile.session.filter(|s| !s.is_expired());
+        if let Some(session) = &self.session {
+            self.player_name = session.name.clone();
+        }
+        self.last_result = None;
+        self.verification = None;
+        self.clear_lobbies();
+        self.active_profile = Some(profile.name.clone());
+        self.profile_name = profile.name.clone();
+        self.status = t!("status-server-used", name = profile.name.as_str());
+        self.check_server();
+    }
+
+    /// 同じ名前のプロファイルは上書きする
+    fn save_profile(&mut self) {
+        let name = self.profile_name.trim().to_string();
+        if name.is_empty() {
+            self.status = t!("status-profile-name-empty");
+            return;
+        }
+        let url = match normalize_url(&self.server_url) {
+            Ok(url) => url,
+            Err(msg) => {
+                self.status = msg;
+                return;
+            }
+        };
+        let profile = ServerProfile {
+            name: name.clone(),
+            url,
+            session: self.session.clone(),
+        };
+        match self.profiles.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+        self.active_profile = Some(name.clone());
+        self.status = t!("status-server-saved", name = name);
+    }
+
+    /// GET /v1/version の往復時間を測る
+    pub(crate) fn ping(&mut self, url: &str) {
+        let Ok(url) = normalize_url(url) else {
+            return;
+        };
+        if matches!(self.pings.get(&url), Some(Ping::Pending)) {
+            return;
+        }
+        self.pings.insert(url.clone(), Ping::Pending);
+
+        let client = self.net.client();
+        let timeout = self.net.settings().request_timeout();
+        let tx = self.tx.clone();
+        self.net.spawn(async move {
+            let started = Instant::now();
+            let resp = client
+                .get(format!("{}{}/version", url, API_PREFIX))
+                .timeout(timeout)
+                .send()
+                .await;
+            let result = match resp {
+                Ok(r) if r.status().is_success() => {
+                    let latency = started.elapsed();
+                    r.json::<ServerInfo>()
+                        .await
+                        .map(|info| (latency, info))
+                        .map_err(|e| t!("parse-json", error = e.to_string()))
+                }
+                Ok(r) => Err(format!("HTTP {}", r.status())),
+                Err(e) => Err(net::describe_request_error(&e)),
+            };
+            let _ = tx.send((String::new(), ClientEvent::Pinged(url, result)));
+        });
+    }
+
+    pub(crate) fn apply_ping(
+        &mut self,
+        url: String,
+        result: Result<(Duration, ServerInfo), String>,
+    ) {
+        let ping = match result {
+            Ok((latency, info)) => Ping::Ok {
+                latency,
+                version: info.server_version,
+            },
+            Err(msg) => Ping::Failed(msg),
+        };
+        self.pings.insert(url, ping);
+    }
+
+    /// 疎通確認・LAN の探索の結果待ちがあるか（再描画用）
+    pub(crate) fn probing(&self) -> bool {
+        #[cfg(feature = "mdns")]
+        if self.discovering {
+            return true;
+        }
+        self.pings.values().any(|p| matches!(p, Ping::Pending))
+    }
+
+    /// 今の URL の疎通確認の結果
+    pub(crate) fn current_ping(&self) -> Option<String> {
+        let url = normalize_url(&self.server_url).ok()?;
+        self.pings.get(&url).map(Ping::label)
+    }
+
+    pub(crate) fn ui_servers(&mut self, ui: &mut egui::Ui) {
+        let current = normalize_url(&self.server_url).ok();
+        let switchable = self.can_switch();
+
+        let mut use_index = None;
+        let mut check = None;
+        let mut delete = None;
+        egui::Grid::new("server_profiles")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                for (i, p) in self.profiles.iter().enumerate() {
+                    let active = self.active_profile.as_ref() == Some(&p.name)
+                        && normalize_url(&p.url).ok() == current;
+                    if active {
+                        ui.strong(&p.name);
+                    } else {
+                        ui.label(&p.name);
+                    }
+                    ui.monospace(&p.url);
+                    let account = match &p.session {
+                        Some(s) if !s.is_expired() => t!("profile-account", name = s.name.as_str()),
+                        _ => String::new(),
+                    };
+                    let ping = normalize_url(&p.url)
+                        .ok()
+                        .and_then(|url| self.pings.get(&url))
+                        .map(Ping::label)
+                        .unwrap_or_default();
+                    ui.small(format!("{} {}", account, ping).trim().to_string());
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(switchable && !active, egui::Button::new(t!("button-use")))
+                            .clicked()
+                        {
+                            use_index = Some(i);
+                        }
+                        if ui.small_button(t!("button-check")).clicked() {
+                            check = Some(p.url.clone());
+                        }
+                        if ui.small_button(t!("button-delete")).clicked() {
+                            delete = Some(i);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        if self.profiles.is_empty() {
+            ui.small(t!("servers-none"));
+        }
+
+        if let Some(i) = use_index {
+            self.use_profile(i);
+        }
+        if let Some(url) = check {
+            self.ping(&url);
+        }
+        if let Some(i) = delete {
+            let removed = self.profiles.remove(i);
+            if self.active_profile.as_ref() == Some(&removed.name) {
+                self.active_profile = None;
+            }
+            self.status = t!("status-server-deleted", name = removed.name);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(t!("label-name"));
+            ui.add(egui::TextEdit::singleline(&mut self.profile_name).desired_width(120.0));
+            if ui.button(t!("button-save-current")).clicked() {
+                self.save_profile();
+            }
+            if ui.button(t!("button-check-all")).clicked() {
+                let urls: Vec<String> = self.profiles.iter().map(|p| p.url.clone()).collect();
+                for url in urls {
+                    self.ping(&url);
+                }
+            }
+        });
+
+        #[cfg(feature = "mdns")]
+        self.ui_discovery(ui);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str) -> Session {
+        Session {
+            name: name.to_string(),
+            token: "token".to_string(),
+            expires_at: u64::MAX,
+        }
+    }
+
+    #[test]
+    fn migrate_profiles_keeps_the_saved_url_and_session() {
+        let mut app = AppState {
+            server_url: "http://battle.example:3000".to_string(),
+            session: Some(session("alice")),
+            ..AppState::default()
+        };
+        app.migrate_profiles();
+
+        assert_eq!(app.profiles.len(), 1);
+        assert_eq!(app.profiles[0].name, "default");
+        assert_eq!(app.profiles[0].url, "http://battle.example:3000");
+        assert_eq!(app.profiles[0].session.as_ref().unwrap().name, "alice");
+        assert_eq!(app.active_profile.as_deref(), Some("default"));
+    }
+
+    #[test]
+    fn migrate_profiles_does_nothing_once_profiles_exist() {
+        let existing = ServerProfile {
+            name: "home".to_string(),
+            url: "http://home:3000".to_string(),
+            session: None,
+        };
+        let mut app = AppState {
+            profiles: vec![existing],
+            active_profile: Some("home".to_string()),
+            ..AppState::default()
+        };
+        app.migrate_profiles();
+
+        assert_eq!(app.profiles.len(), 1);
+        assert_eq!(app.profiles[0].name, "home");
+        assert_eq!(app.active_profile.as_deref(), Some("home"));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::character::Preset;
+use crate::AppState;
+use battle_api::roster::{Roster, RosterCharacter};
+use battle_api::{capability, t, JoinResponse, SquadMember, StatBudget};
+use eframe::egui;
+
+// ===== スクワッド（1回の参加で一緒に入るキャラクター） =====
+//
+// 本人のキャラクターに加えて、プリセットを名前付きで並べておき JoinRequest::squad で送る。
+// サーバ上の名前は "<アカウント名>/<名前>" になり、結果もキャラクターごとに返る。
+
+/// サーバと同じ制限（1-16 文字の英数字と '_' '-'）
+fn is_valid_member_name(name: &str) -> bool {
+    (1..=16).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 結果に出てくる自分のキャラクターの名前（本人、squad の順）
+pub fn own_names(result: &JoinResponse) -> Vec<String> {
+    std::iter::once(result.name.clone())
+        .chain(result.squad.iter().map(|r| r.name.clone()))
+        .collect()
+}
+
+/// ロスターファイルにする（本人、squad の順）
+///
+/// サーバ上の "<アカウント名>/<名前>" はロスターの名前の上限（MAX_NAME_CHARS）を超えうるので、
+/// squad は squad 内の名前だけで書き出す
+pub fn roster_file(
+    account: &str,
+    character: &Preset,
+    squad: &[Preset],
+    budget: &StatBudget,
+) -> Roster {
+    let characters = std::iter::once((account, character))
+        .chain(squad.iter().map(|p| (p.name.as_str(), p)))
+        .map(|(name, preset)| {
+            let (hp, atk) = preset.base_stats(budget);
+            RosterCharacter {
+                name: name.to_string(),
+                hp,
+                atk,
+                class: preset.class,
+                targeting: preset.targeting,
+            }
+        })
+        .collect();
+    Roster::new(characters)
+}
+
+impl AppState {
+    pub(crate) fn supports_squad(&self) -> bool {
+        self.server_info
+            .as_ref()
+            .is_some_and(|info| info.supports(capability::SQUAD))
+    }
+
+    /// 本人を含めた人数
+    pub(crate) fn squad_size(&self) -> usize {
+        1 + self.squad.len()
+    }
+
+    /// 送る squad（対応していないサーバには送らない）
+    pub(crate) fn squad_members(&self, budget: &StatBudget) -> Vec<SquadMember> {
+        if !self.supports_squad() {
+            return Vec::new();
+        }
+        self.squad
+            .iter()
+            .map(|p| {
+                let (hp, atk) = p.base_stats(budget);
+                SquadMember {
+                    name: p.name.clone(),
+                    hp,
+                    atk,
+                    class: p.class,
+                    targeting: p.targeting,
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn add_to_squad(&mut self, preset: Preset) {
+        let name = preset.name.trim().to_string();
+        if !is_valid_member_name(&name) {
+            self.status = t!("status-squad-invalid-name");
+            return;
+        }
+        if self.squad.iter().any(|p| p.name == name) {
+            self.status = t!("status-squad-duplicate", name = name.as_str());
+            return;
+        }
+        self.status = t!("status-squad-added", name = name.as_str());
+        self.squad.push(Preset { name, ..preset });
+    }
+
+    /// キャラクター作成画面の一番下に出す
+    pub(crate) fn ui_squad(&mut self, ui: &mut egui::Ui, budget: &StatBudget) {
+        ui.label(t!("squad-heading", count = self.squad.len()));
+        if !self.supports_squad() && !self.squad.is_empty() {
+            ui.small(t!("squad-unsupported"));
+        }
+
+        let mut remove = None;
+        for (i, member) in self.squad.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.monospace(&member.name);
+                ui.label(member.summary(budget));
+                if ui.small_button(t!("button-remove")).clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            let removed = self.squad.remove(i);
+            self.status = t!("status-squad-removed", name = removed.name);
+        }
+
+        ui.horizontal(|ui| {
+            let add = ui
+                .button(t!("button-add-current"))
+                .on_hover_text(t!("hover-add-current"));
+            if add.clicked() {
+                let preset = Preset {
+                    name: self.preset_name.clone(),
+                    ..self.character.clone()
+                };
+                self.add_to_squad(preset);
+            }
+            if !self.squad.is_empty() && ui.button(t!("button-clear")).clicked() {
+                self.squad.clear();
+            }
+        });
+    }
+
+    /// 参加前にロビー画面で出す1行
+    pub(crate) fn ui_squad_summary(&mut self, ui: &mut egui::Ui) {
+        if self.squad.is_empty() {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label(t!("label-squad"));
+            let names: Vec<&str> = self.squad.iter().map(|p| p.name.as_str()).collect();
+            ui.monospace(names.join(", "));
+            if ui.small_button(t!("button-edit")).clicked() {
+                self.screen = crate::Screen::Character;
+            }
+        });
+    }
+}
+
+/// 結果の表（本人と squad）
+pub fn ui_results(ui: &mut egui::Ui, result: &JoinResponse) {
+    egui::Grid::new("squad_results")
+        .striped(true)
+        .num_columns(4)
+        .show(ui, |ui| {
+            ui.strong(t!("col-character"));
+            ui.strong(t!("col-rank"));
+            ui.strong(t!("col-final-hp"));
+            ui.strong("");
+            ui.end_row();
+            let own = (&result.name, result.rank, result.final_hp, result.is_winner);
+            let squad = result
+                .squad
+                .iter()
+                .map(|r| (&r.name, r.rank, r.final_hp, r.is_winner));
+            for (name, rank, final_hp, is_winner) in std::iter::once(own).chain(squad) {
+                ui.monospace(name);
+                ui.monospace(rank.to_string());
+                ui.monospace(final_hp.to_string());
+                ui.label(if is_winner {
+                    t!("result-winner")
+                } else {
+                    String::new()
+                });
+                ui.end_row();
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use battle_api::roster::MAX_NAME_CHARS;
+    use battle_api::Targeting;
+
+    fn preset(name: &str, targeting: Targeting) -> Preset {
+        Preset {
+            name: name.to_string(),
+            targeting,
+            ..Preset::default()
+        }
+    }
+
+    #[test]
+    fn exported_roster_is_accepted_with_the_longest_names() {
+        let account = "a".repeat(MAX_NAME_CHARS);
+        let squad = [preset(&"m".repeat(16), Targeting::Strongest)];
+        let roster = roster_file(
+            &account,
+            &preset("me", Targeting::Weakest),
+            &squad,
+            &StatBudget::default(),
+        );
+        roster.validate().unwrap();
+
+        let targeting: Vec<Targeting> = roster.characters.iter().map(|c| c.targeting).collect();
+        assert_eq!(targeting, [Targeting::Weakest, Targeting::Strongest]);
+        assert_eq!(roster.characters[0].name, account);
+    }
+}
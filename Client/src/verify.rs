@@ -0,0 +1,98 @@
+use crate::AppState;
+use battle_api::engine::{self, Mismatch, Verification};
+use battle_api::{t, BattleLog};
+use eframe::egui;
+
+// ===== 結果の検証 =====
+//
+// 自分のキャラクター（本人と squad）について、バトルログのステータスが送ったとおりか、
+// ログの seed とロスターで battle_api::engine を手元で回し直した順位と最終 hp が
+// サーバの答えと同じかを確かめる。
+
+impl AppState {
+    /// 直近の結果のログが届いたら確かめる
+    pub(crate) fn verify_result(&mut self, log: &BattleLog) {
+        let (Some(request), Some(result)) = (
+            self.last_request.as_ref(),
+            self.last_result
+                .as_ref()
+                .filter(|r| r.match_id == Some(log.match_id)),
+        ) else {
+            return;
+        };
+        let verification = engine::verify(request, result, log);
+        if matches!(verification, Verification::Mismatch(_)) {
+            self.status = t!("status-verify-mismatch", id = log.match_id);
+        }
+        self.verification = Some(verification);
+    }
+
+    /// ログを取れなかったときは確かめられない
+    pub(crate) fn mark_unverifiable(&mut self, match_id: u64) {
+        if self
+            .last_result
+            .as_ref()
+            .is_some_and(|r| r.match_id == Some(match_id))
+        {
+            self.verification = Some(Verification::Unverifiable);
+        }
+    }
+
+    /// 結果の下に出す
+    pub(crate) fn ui_verification(&self, ui: &mut egui::Ui) {
+        match &self.verification {
+            Some(Verification::Verified) => {
+                ui.colored_label(egui::Color32::from_rgb(60, 170, 60), t!("verify-ok"));
+            }
+            Some(Verification::Mismatch(mismatches)) => {
+                ui.colored_label(ui.visuals().error_fg_color, t!("verify-mismatch"));
+                for m in mismatches {
+                    ui.monospace(describe(m));
+                }
+            }
+            Some(Verification::Unverifiable) => {
+                ui.small(t!("verify-unverifiable"));
+            }
+            None if self.log_fetch.is_some() => {
+                ui.small(t!("verify-pending"));
+            }
+            None => {}
+        }
+    }
+}
+
+fn describe(m: &Mismatch) -> String {
+    match m {
+        Mismatch::Stats {
+            name,
+            sent: (hp, atk, targeting),
+            logged: Some((logged_hp, logged_atk, logged_targeting)),
+        } => t!(
+            "verify-stats-line",
+            name = name.as_str(),
+            hp = *hp,
+            atk = *atk,
+            targeting = targeting.as_str(),
+            logged_hp = *logged_hp,
+            logged_atk = *logged_atk,
+            logged_targeting = logged_targeting.as_str()
+        ),
+        Mismatch::Stats {
+            name, logged: None, ..
+        } => {
+            t!("verify-missing-line", name = name.as_str())
+        }
+        Mismatch::Result {
+            name,
+            server: (rank, final_hp),
+            local: (local_rank, local_final_hp),
+        } => t!(
+            "verify-mismatch-line",
+            name = name.as_str(),
+            rank = *rank,
+            final_hp = *final_hp,
+            local_rank = *local_rank,
+            local_final_hp = *local_final_hp
+        ),
+    }
+}
@@ -0,0 +1,106 @@
+use battle_server::engine::{self, Fighter, Targeting};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// 旧実装（攻撃のたびに生存者リストを作り直す）。比較用にそのまま残している
+fn naive_run_battle<R: Rng>(mut chars: Vec<Fighter>, rng: &mut R) -> Vec<usize> {
+    let mut is_alive = vec![true; chars.len()];
+    let mut death_order: Vec<usize> = Vec::new();
+
+    loop {
+        let alive_indices: Vec<usize> = is_alive
+            .iter()
+            .enumerate()
+            .filter(|(_, alive)| **alive)
+            .map(|(i, _)| i)
+            .collect();
+
+        if alive_indices.len() <= 1 {
+            break;
+        }
+
+        let attacker_idx = alive_indices[rng.gen_range(0..alive_indices.len())];
+        let mut defender_idx = attacker_idx;
+        while defender_idx == attacker_idx {
+            defender_idx = alive_indices[rng.gen_range(0..alive_indices.len())];
+        }
+
+        chars[defender_idx].hp -= chars[attacker_idx].atk;
+        if chars[defender_idx].hp <= 0 {
+            is_alive[defender_idx] = false;
+            death_order.push(defender_idx);
+        }
+    }
+
+    death_order.extend((0..chars.len()).filter(|&i| is_alive[i]));
+    death_order
+}
+
+fn roster(count: usize) -> Vec<Fighter> {
+    roster_with(count, |_| Targeting::Random)
+}
+
+/// Random / Weakest / Strongest を順番に割り当てる
+fn targeted_roster(count: usize) -> Vec<Fighter> {
+    roster_with(count, |i| Targeting::ALL[i % Targeting::ALL.len()])
+}
+
+fn roster_with(count: usize, targeting: impl Fn(usize) -> Targeting) -> Vec<Fighter> {
+    let mut rng = StdRng::seed_from_u64(count as u64);
+    (0..count)
+        .map(|i| Fighter {
+            name: format!("C{}", i),
+            hp: rng.gen_range(50..=100),
+            atk: rng.gen_range(20..=40),
+            is_client: false,
+            targeting: targeting(i),
+        })
+        .collect()
+}
+
+fn bench_battle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("battle");
+    group.sample_size(10);
+
+    for count in [100usize, 10_000, 1_000_000] {
+        let chars = roster(count);
+
+        // 旧実装は 1M 人だと O(n^2) で終わらないので 10k までにする
+        if count <= 10_000 {
+            group.bench_with_input(BenchmarkId::new("naive", count), &chars, |b, chars| {
+                let mut rng = StdRng::seed_from_u64(1);
+                b.iter_batched(
+                    || chars.clone(),
+                    |chars| naive_run_battle(chars, &mut rng),
+                    BatchSize::LargeInput,
+                );
+            });
+        }
+
+        group.bench_with_input(BenchmarkId::new("alive_set", count), &chars, |b, chars| {
+            let mut rng = StdRng::seed_from_u64(1);
+            b.iter_batched(
+                || chars.clone(),
+                |chars| engine::run_battle(chars, &mut rng),
+                BatchSize::LargeInput,
+            );
+        });
+
+        // Weakest / Strongest は TargetIndex で相手を決める（全員 Random のときは作らない）
+        let chars = targeted_roster(count);
+        group.bench_with_input(BenchmarkId::new("targeted", count), &chars, |b, chars| {
+            let mut rng = StdRng::seed_from_u64(1);
+            b.iter_batched(
+                || chars.clone(),
+                |chars| engine::run_battle(chars, &mut rng),
+                BatchSize::LargeInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_battle);
+criterion_main!(benches);
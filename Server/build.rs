@@ -0,0 +1,12 @@
+// proto/battle.proto から gRPC のコードを生成する（grpc feature のときだけ）
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/battle.proto");
+        // protoc をインストールしなくてもビルドできるように同梱版を使う
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc not found");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::compile_protos("proto/battle.proto").expect("failed to compile battle.proto");
+    }
+}
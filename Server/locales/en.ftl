@@ -0,0 +1,45 @@
+# battle_server のログと hello_world の出力（英語）
+# 数値は Fluent の数値としてそのまま出る。seed など大きな値は文字列で渡す
+
+## サーバの起動
+
+server-random-jwt-secret = BATTLE_JWT_SECRET is not set; using a random key
+server-grpc-listening = gRPC listening on { $addr }
+server-grpc-error = gRPC server error: { $error }
+server-matchmaking-enabled = Matchmaking API enabled (/v1/join)
+server-instant-enabled = Instant battle API enabled (/v1/battle)
+server-listening = Server listening on { $addr }
+server-mdns-failed = mDNS advertisement failed: { $error }
+server-mdns-advertising = Advertising { $instance } over mDNS ({ $service })
+config-unknown-abandon-policy = unknown BATTLE_ABANDON_POLICY '{ $value }', using '{ $default }'
+config-invalid-value = invalid value for { $key }: '{ $value }', using default
+error-internal = internal error: { $error }
+
+## アカウント・ロビー
+
+auth-registered = Registered account { $name }
+lobby-joined = { $name } joined { $mode } lobby #{ $lobby } ({ $characters ->
+    [one] 1 character
+   *[other] { $characters } characters
+})
+lobby-cancelled = { $name } cancelled their join
+lobby-disconnected = { $name } disconnected while waiting
+lobby-result-undeliverable = Could not send the result to { $name } (disconnected)
+
+## hello_world
+
+hello-generated = { $count } characters generated! (seed = { $seed })
+hello-turn = --- Turn { $turn } ---
+hello-attack = { $attacker } dealt { $damage } damage to { $defender }!
+hello-death = { $name } fell!
+hello-turns = The battle was decided in { $turns } turns
+hello-standing = { $rank }.  { $name }  (hp={ $hp }, atk={ $atk }, final hp={ $final_hp })
+hello-winner = The last survivor is { $name }!
+hello-no-winner = Everyone fell...
+hello-invalid-roster = invalid roster: { $error }
+hello-roster-too-small = roster needs at least 2 characters
+hello-invalid-range = stat range min must not exceed max
+hello-invalid-stats = hp must be positive and atk must not be negative
+hello-count-too-small = count must be at least 2
+cli-write-failed = failed to write output: { $error }
+cli-error = error: { $message }
@@ -0,0 +1,41 @@
+# battle_server のログと hello_world の出力（日本語）
+
+## サーバの起動
+
+server-random-jwt-secret = BATTLE_JWT_SECRET が未設定のためランダムな鍵を使います
+server-grpc-listening = gRPC を { $addr } で待ち受けています
+server-grpc-error = gRPC サーバのエラー: { $error }
+server-matchmaking-enabled = マッチング API を有効にしました（/v1/join）
+server-instant-enabled = 即時バトル API を有効にしました（/v1/battle）
+server-listening = { $addr } で待ち受けています
+server-mdns-failed = mDNS で広告できませんでした: { $error }
+server-mdns-advertising = mDNS で { $instance } を広告しています（{ $service }）
+config-unknown-abandon-policy = BATTLE_ABANDON_POLICY の '{ $value }' は使えないため '{ $default }' にします
+config-invalid-value = { $key } の値 '{ $value }' が不正なためデフォルトを使います
+error-internal = 内部エラー: { $error }
+
+## アカウント・ロビー
+
+auth-registered = アカウント { $name } を登録しました
+lobby-joined = { $name }がマッチに参加しました（{ $characters }人、{ $mode } ロビー #{ $lobby }）
+lobby-cancelled = { $name }が参加を取り消しました
+lobby-disconnected = { $name }が待機中に切断しました
+lobby-result-undeliverable = { $name }に結果を送れませんでした（切断済み）
+
+## hello_world
+
+hello-generated = { $count } 体のキャラクターが生成されました！（seed = { $seed }）
+hello-turn = --- { $turn } ターン目 ---
+hello-attack = { $attacker } が { $defender } に { $damage } ダメージ与えた！
+hello-death = { $name } が倒れた！
+hello-turns = { $turns } ターンで決着しました
+hello-standing = { $rank } 位  { $name }  (hp={ $hp }, atk={ $atk }, 最終hp={ $final_hp })
+hello-winner = 最後の生き残りは { $name } です！
+hello-no-winner = 全滅しました…
+hello-invalid-roster = ロスターが不正です: { $error }
+hello-roster-too-small = ロスターには2人以上必要です
+hello-invalid-range = ステータスの範囲は min ≤ max にしてください
+hello-invalid-stats = hp は正、atk は 0 以上にしてください
+hello-count-too-small = キャラクター数は2以上にしてください
+cli-write-failed = 出力に失敗しました: { $error }
+cli-error = エラー: { $message }
@@ -1,136 +1,363 @@
+// オフライン用のバトルシミュレータ
+//
+//   cargo run --bin hello_world -- --count 500 --seed 42 --verbosity deaths
+//   cargo run --bin hello_world -- --roster roster.toml --format json
+//   cargo run --bin hello_world -- --lang en
 
+use battle_api::i18n::Locale;
+use battle_api::t;
+use battle_server::engine::{self, BattleEvent, Fighter, StatRange, Targeting};
+use battle_server::roster::Roster;
+use clap::{Parser, ValueEnum};
 use rand::Rng; // 乱数用
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-struct Character {
+// ===== コマンドライン =====
+
+#[derive(Parser)]
+#[command(about = "Run a single battle royale and print what happens")]
+struct Args {
+    /// キャラクター数（省略時は 1000〜10000 のランダム）
+    #[arg(long, conflicts_with = "roster")]
+    count: Option<usize>,
+
+    /// 乱数シード（省略時はランダム。結果に表示されるので再現に使える）
+    #[arg(long)]
+    seed: Option<u64>,
+
+    #[arg(long, default_value_t = 50)]
+    hp_min: i32,
+    #[arg(long, default_value_t = 100)]
+    hp_max: i32,
+    #[arg(long, default_value_t = 20)]
+    atk_min: i32,
+    #[arg(long, default_value_t = 40)]
+    atk_max: i32,
+
+    /// ランダム生成するキャラの名前の長さ
+    #[arg(long, default_value_t = 5)]
+    name_len: usize,
+
+    /// キャラクターをロスターファイル（.json / .toml）から読む
+    #[arg(long)]
+    roster: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t = Verbosity::Attacks)]
+    verbosity: Verbosity,
+
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// text 出力の言語（en / ja、省略時は BATTLE_LANG / LANG）
+    #[arg(long)]
+    lang: Option<Locale>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Verbosity {
+    /// 全ての攻撃を出す
+    Attacks,
+    /// 倒れたキャラだけ出す
+    Deaths,
+    /// 結果だけ出す
+    Summary,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+// ===== 入出力の型 =====
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum LogEntry {
+    Attack {
+        turn: u64,
+        attacker: String,
+        defender: String,
+        damage: i32,
+        defender_hp: i32,
+    },
+    Death {
+        turn: u64,
+        name: String,
+    },
+}
+
+#[derive(Serialize)]
+struct Standing {
+    rank: usize,
     name: String,
     hp: i32,
     atk: i32,
-    is_live: bool,
+    final_hp: i32,
 }
 
-impl Character {
-    // コンストラクタ相当
-    fn new(name: &str) -> Self {
-        let mut rng = rand::thread_rng();
+#[derive(Serialize)]
+struct Report {
+    seed: u64,
+    characters: usize,
+    turns: u64,
+    winner: Option<String>,
+    standings: Vec<Standing>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    events: Vec<LogEntry>,
+}
 
-        let hp = rng.gen_range(50..=100);
-        let atk = rng.gen_range(20..=40);
+// ===== キャラクター生成 =====
 
-        let me = Self {
-            name: name.to_string(),
-            hp,
-            atk,
-            is_live: true,
-        };
+fn load_roster(path: &Path) -> Result<Vec<Fighter>, String> {
+    let roster =
+        Roster::load(path).map_err(|e| t!("hello-invalid-roster", error = e.to_string()))?;
+    if roster.characters.len() < 2 {
+        return Err(t!("hello-roster-too-small"));
+    }
+    Ok(roster.to_fighters(false))
+}
 
-        // println!("{}: hp={}, atk={}", me.name, me.hp, me.atk);
+fn random_roster(args: &Args, rng: &mut impl Rng) -> Result<Vec<Fighter>, String> {
+    let hp = StatRange::new(args.hp_min, args.hp_max);
+    let atk = StatRange::new(args.atk_min, args.atk_max);
+    if !hp.is_valid() || !atk.is_valid() {
+        return Err(t!("hello-invalid-range"));
+    }
+    if hp.min <= 0 || atk.min < 0 {
+        return Err(t!("hello-invalid-stats"));
+    }
 
-        me
+    let count = args.count.unwrap_or_else(|| rng.gen_range(1000..=10000));
+    if count < 2 {
+        return Err(t!("hello-count-too-small"));
     }
 
-    fn damage(&mut self, amount: i32) {
-        // ダメージを受ける
-        self.hp -= amount;
+    Ok((0..count)
+        .map(|_| Fighter {
+            name: random_name(rng, args.name_len),
+            hp: hp.sample(rng),
+            atk: atk.sample(rng),
+            is_client: false,
+            targeting: Targeting::Random,
+        })
+        .collect())
+}
 
-        // HPが0以下になったら死亡判定
-        if self.hp <= 0 {
-            self.is_live = false;
-        }
-    }
+fn random_name<R: Rng>(rng: &mut R, len: usize) -> String {
+    (0..len)
+        .map(|_| {
+            let c = rng.gen_range(b'A'..=b'Z'); // A〜Z
+            c as char
+        })
+        .collect()
+}
 
-    fn attack(&self, target: &mut Character) {
-        // すでに死んでいるなら何もしない
-        if !self.is_live {
-            return;
-        }
+// ===== 実行 =====
 
-        println!(
-            "{} が {} に {} ダメージ与えた！",
-            self.name, target.name, self.atk
-        );
+fn run(args: Args) -> Result<(), String> {
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    // サーバと同じ乱数にして、表示した seed がビルドをまたいでも同じ結果になるようにする
+    let mut rng = engine::battle_rng(seed);
+
+    let fighters = match &args.roster {
+        Some(path) => load_roster(path)?,
+        None => random_roster(&args, &mut rng)?,
+    };
+    let initial: Vec<(i32, i32)> = fighters.iter().map(|f| (f.hp, f.atk)).collect();
+    let names: Vec<String> = fighters.iter().map(|f| f.name.clone()).collect();
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let text = args.format == Format::Text;
 
-        target.damage(self.atk);
+    if text {
+        // seed は f64 に収まらないので文字列で渡す
+        let _ = writeln!(
+            out,
+            "{}",
+            t!(
+                "hello-generated",
+                count = fighters.len(),
+                seed = seed.to_string()
+            )
+        );
     }
-}
 
-fn main() {
-    let mut rng = rand::thread_rng();
-    let count = rng.gen_range(1000..=10000);
+    // テキストはその場で出し、JSON / CSV は最後にまとめて出す
+    let mut turn = 0u64;
+    let mut events = Vec::new();
+    let outcome = engine::run_battle_with(fighters, &mut rng, |event| match *event {
+        BattleEvent::Attack {
+            attacker,
+            defender,
+            damage,
+            defender_hp,
+        } => {
+            turn += 1;
+            if args.verbosity != Verbosity::Attacks {
+                return;
+            }
+            if text {
+                let _ = writeln!(out, "{}", t!("hello-turn", turn = turn));
+                let _ = writeln!(
+                    out,
+                    "{}",
+                    t!(
+                        "hello-attack",
+                        attacker = names[attacker].as_str(),
+                        defender = names[defender].as_str(),
+                        damage = damage
+                    )
+                );
+            } else {
+                events.push(LogEntry::Attack {
+                    turn,
+                    attacker: names[attacker].clone(),
+                    defender: names[defender].clone(),
+                    damage,
+                    defender_hp,
+                });
+            }
+        }
+        BattleEvent::Death { index } => {
+            if args.verbosity == Verbosity::Summary {
+                return;
+            }
+            if text {
+                let _ = writeln!(out, "{}", t!("hello-death", name = names[index].as_str()));
+            } else {
+                events.push(LogEntry::Death {
+                    turn,
+                    name: names[index].clone(),
+                });
+            }
+        }
+    });
 
-    let mut chars: Vec<Character> = (0..count)
-        .map(|_| {
-            let name = random_name(5);
-            Character::new(&name)
+    let ranks = outcome.ranks();
+    let standings: Vec<Standing> = outcome
+        .standings()
+        .map(|i| Standing {
+            rank: ranks[i],
+            name: names[i].clone(),
+            hp: initial[i].0,
+            atk: initial[i].1,
+            final_hp: outcome.fighters[i].hp,
         })
         .collect();
-    println!("{} 体のキャラクターが生成されました！", chars.len());
-
-    // ここからバトルループを作っていく
-    let mut rng = rand::thread_rng();
-
-    for turn in 0.. {
-        println!("--- {} ターン目 ---", turn + 1);
-        {
-            let i = rng.gen_range(0..chars.len());
-            let mut j = rng.gen_range(0..chars.len());
-            while j == i {
-                j = rng.gen_range(0..chars.len());
-            }
 
-            let (attacker, defender) = two_mut(&mut chars, i, j);
-            attacker.attack(defender);
-        }
+    let report = Report {
+        seed,
+        characters: names.len(),
+        turns: turn,
+        winner: outcome.winner().map(|w| w.name.clone()),
+        standings,
+        events,
+    };
 
-        let dead_names: Vec<String> = chars
-            .iter()
-            .filter(|c| c.hp <= 0)
-            .map(|c| c.name.clone())
-            .collect();
+    write_report(&mut out, &report, args.format, args.verbosity)
+        .map_err(|e| t!("cli-write-failed", error = e.to_string()))
+}
 
-        // 死んだキャラを Vec から削除
-        chars.retain(|c| c.hp > 0);
-        for name in &dead_names {
-            println!("{} が倒れた！", name);
+fn write_report(
+    out: &mut dyn Write,
+    report: &Report,
+    format: Format,
+    verbosity: Verbosity,
+) -> std::io::Result<()> {
+    match format {
+        Format::Text => {
+            if verbosity == Verbosity::Summary {
+                writeln!(out, "{}", t!("hello-turns", turns = report.turns))?;
+                for s in report.standings.iter().take(10) {
+                    // 順位の桁揃えは Fluent ではできないので先に整形する
+                    let line = t!(
+                        "hello-standing",
+                        rank = format!("{:>5}", s.rank),
+                        name = s.name.as_str(),
+                        hp = s.hp,
+                        atk = s.atk,
+                        final_hp = s.final_hp
+                    );
+                    writeln!(out, "{}", line)?;
+                }
+            }
+            match &report.winner {
+                Some(winner) => writeln!(out, "{}", t!("hello-winner", name = winner.as_str()))?,
+                None => writeln!(out, "{}", t!("hello-no-winner"))?,
+            }
         }
-
-        // 生き残りが1人以下なら終了
-        if chars.len() <= 1 {
-            if let Some(winner) = chars.first() {
-                println!("最後の生き残りは {} です！", winner.name);
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, report)?;
+            writeln!(out)?;
+        }
+        Format::Csv => {
+            // summary は順位表、それ以外はイベントログ
+            if verbosity == Verbosity::Summary {
+                writeln!(out, "rank,name,hp,atk,final_hp")?;
+                for s in &report.standings {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{}",
+                        s.rank,
+                        csv_field(&s.name),
+                        s.hp,
+                        s.atk,
+                        s.final_hp
+                    )?;
+                }
             } else {
-                println!("全滅しました…");
+                writeln!(out, "turn,event,attacker,defender,damage,defender_hp")?;
+                for e in &report.events {
+                    match e {
+                        LogEntry::Attack {
+                            turn,
+                            attacker,
+                            defender,
+                            damage,
+                            defender_hp,
+                        } => writeln!(
+                            out,
+                            "{},attack,{},{},{},{}",
+                            turn,
+                            csv_field(attacker),
+                            csv_field(defender),
+                            damage,
+                            defender_hp
+                        )?,
+                        LogEntry::Death { turn, name } => {
+                            writeln!(out, "{},death,,{},,", turn, csv_field(name))?
+                        }
+                    }
+                }
             }
-            break;
         }
     }
+    Ok(())
 }
 
-fn two_mut<T>(slice: &mut [T], i: usize, j: usize) -> (&mut T, &mut T) {
-    assert!(i != j);
-
-    if i < j {
-        // [0 .. j) と [j .. end) に分割
-        let (first, rest) = slice.split_at_mut(j);
-        let attacker = &mut first[i]; // i 番目
-        let defender = &mut rest[0]; // j 番目
-        (attacker, defender)
+/// ロスターの名前にカンマ等が入っていてもいいようにクォートする
+fn csv_field(s: &str) -> std::borrow::Cow<'_, str> {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\"")).into()
     } else {
-        // i > j の場合：同じことを j / i でやる
-        let (first, rest) = slice.split_at_mut(i);
-        let attacker = &mut rest[0]; // i 番目
-        let defender = &mut first[j]; // j 番目
-        (attacker, defender)
+        s.into()
     }
 }
 
-fn random_name(len: usize) -> String {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-
-    (0..len)
-        .map(|_| {
-            let c = rng.gen_range(b'A'..=b'Z'); // A〜Z
-            c as char
-        })
-        .collect()
+fn main() -> ExitCode {
+    let args = Args::parse();
+    battle_server::i18n::init(args.lang);
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("{}", t!("cli-error", message = msg));
+            ExitCode::from(2)
+        }
+    }
 }
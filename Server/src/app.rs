@@ -0,0 +1,38 @@
+use crate::auth::{self, SharedAuth};
+use crate::config::ServerConfig;
+use crate::lobby::{self, SharedLobby};
+use crate::{instant, protocol};
+use axum::{extract::DefaultBodyLimit, middleware, Router};
+use battle_api::API_PREFIX;
+
+// ===== HTTP のルーター =====
+//
+// マッチング（lobby）と即時バトル（instant）を /v1 以下にまとめる。
+// main.rs と結合テスト（tests/http.rs）で同じものを使う。
+
+/// lobby が None ならマッチングと /register・/login を公開しない
+pub fn router(config: &ServerConfig, lobby: Option<SharedLobby>, auth: SharedAuth) -> Router {
+    let mut v1 = Router::new();
+    if let Some(lobby) = lobby {
+        v1 = v1
+            .merge(lobby::routes(lobby, auth.clone()))
+            .merge(auth::routes(auth, config));
+    }
+    if config.enable_instant {
+        v1 = v1.merge(instant::routes(config));
+    }
+
+    // /v1/version だけはプロトコルの確認をしない
+    let v1 = v1
+        .layer(middleware::from_fn(protocol::check_protocol))
+        .merge(protocol::routes(config));
+
+    let app = Router::new()
+        .nest(API_PREFIX, v1)
+        .merge(protocol::legacy_routes());
+
+    #[cfg(feature = "openapi")]
+    let app = app.merge(crate::openapi::routes(config));
+
+    app.layer(DefaultBodyLimit::max(config.max_body_bytes))
+}
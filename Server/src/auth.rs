@@ -0,0 +1,320 @@
+use crate::config::ServerConfig;
+use crate::error::{AppError, AppJson};
+use crate::rate_limit::{self, RateLimiter, RouteLimits};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::{
+    extract::{Request, State},
+    http::header::AUTHORIZATION,
+    middleware::{self, Next},
+    response::Response,
+    routing::post,
+    Router,
+};
+use battle_api::{t, CredentialsRequest, TokenResponse};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+// ===== アカウントとトークン =====
+//
+// アカウントはこのプロセスのメモリにだけある。再起動すると消え、レプリカ間でも共有されない。
+// JWT の鍵（BATTLE_JWT_SECRET）を共有したまま複数レプリカにすると、別のレプリカで同じ名前を
+// 登録してその名前のトークンを発行できてしまう（verify_token はアカウントの有無を見ない）。
+// そのため deployment.yml はレプリカ 1 つで動かす。増やすならアカウントを共有のストアに移すこと。
+
+struct Account {
+    password_hash: String, // argon2 の PHC 文字列
+}
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: String, // アカウント名
+    exp: u64,
+}
+
+/// 認証済みのプレイヤー（ミドルウェアが request extensions に入れる）
+#[derive(Clone, Debug)]
+pub struct AuthUser {
+    pub name: String,
+}
+
+/// トークンの発行・検証とアカウント（プロセス内のみ、上の注意を参照）
+pub struct Auth {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    token_ttl: Duration,
+    accounts: Mutex<HashMap<String, Account>>,
+}
+
+pub type SharedAuth = Arc<Auth>;
+
+impl Auth {
+    pub fn new(secret: &[u8], token_ttl: Duration) -> Self {
+        Self {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            token_ttl,
+            accounts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 署名済みトークンを発行する（gRPC の結合テストなどからも使う）
+    pub fn issue_token(&self, name: &str) -> Result<TokenResponse, AppError> {
+        let expires_at = unix_now() + self.token_ttl.as_secs();
+        let claims = Claims {
+            sub: name.to_string(),
+            exp: expires_at,
+        };
+        let token = jsonwebtoken::encode(&Header::default(), &claims, &self.encoding)
+            .map_err(|e| AppError::Internal(format!("failed to sign token: {}", e)))?;
+
+        Ok(TokenResponse {
+            name: name.to_string(),
+            token,
+            expires_at,
+        })
+    }
+
+    /// Bearer トークンを検証してアカウント名を返す
+    pub fn verify_token(&self, token: &str) -> Result<AuthUser, AppError> {
+        let data = jsonwebtoken::decode::<Claims>(token, &self.decoding, &Validation::default())
+            .map_err(|_| AppError::Unauthorized("invalid or expired token".to_string()))?;
+        Ok(AuthUser {
+            name: data.claims.sub,
+        })
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn validate_credentials(req: &CredentialsRequest) -> Result<(), AppError> {
+    let name_ok = (1..=32).contains(&req.name.chars().count())
+        && req
+            .name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if !name_ok {
+        return Err(AppError::invalid_field(
+            "name",
+            "name must be 1-32 letters, digits, '_' or '-'",
+        ));
+    }
+    if req.password.chars().count() < 8 {
+        return Err(AppError::invalid_field(
+            "password",
+            "password must be at least 8 characters",
+        ));
+    }
+    Ok(())
+}
+
+// argon2 は重いので blocking スレッドで回す
+async fn hash_password(password: String) -> Result<String, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|h| h.to_string())
+            .map_err(|e| AppError::Internal(format!("failed to hash password: {}", e)))
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("hash task failed: {}", e)))?
+}
+
+/// 存在しないアカウントへの /login でも同じだけ時間をかけるための照合先。
+/// hash_password と同じ Argon2::default() のパラメータで作ってある
+const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$LUPgCoP+jzZm83YAEqISZQ$AQZ1BCW6qZxhW1KP79miF7t2y84c3AJ1EB61ekJ6qZg";
+
+async fn verify_password(password: String, password_hash: String) -> bool {
+    tokio::task::spawn_blocking(move || {
+        PasswordHash::new(&password_hash)
+            .map(|parsed| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false)
+    })
+    .await
+    .unwrap_or(false)
+}
+
+// ===== /register /login ハンドラ =====
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/v1/register",
+    tag = "auth",
+    request_body = CredentialsRequest,
+    responses(
+        (status = 200, description = "account created", body = TokenResponse),
+        (status = 409, description = "name already taken", body = ErrorBody),
+        (status = 422, description = "invalid name or password", body = ErrorBody),
+    )
+))]
+async fn register_handler(
+    State(auth): State<SharedAuth>,
+    AppJson(req): AppJson<CredentialsRequest>,
+) -> Result<AppJson<TokenResponse>, AppError> {
+    validate_credentials(&req)?;
+
+    if auth.accounts.lock().await.contains_key(&req.name) {
+        return Err(AppError::Conflict(format!(
+            "account '{}' already exists",
+            req.name
+        )));
+    }
+
+    let password_hash = hash_password(req.password).await?;
+
+    {
+        // ハッシュ中に同名で登録されていないか確認し直す
+        let mut accounts = auth.accounts.lock().await;
+        if accounts.contains_key(&req.name) {
+            return Err(AppError::Conflict(format!(
+                "account '{}' already exists",
+                req.name
+            )));
+        }
+        accounts.insert(req.name.clone(), Account { password_hash });
+    }
+
+    println!("{}", t!("auth-registered", name = req.name.as_str()));
+    Ok(AppJson(auth.issue_token(&req.name)?))
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/v1/login",
+    tag = "auth",
+    request_body = CredentialsRequest,
+    responses(
+        (status = 200, description = "logged in", body = TokenResponse),
+        (status = 401, description = "invalid name or password", body = ErrorBody),
+    )
+))]
+async fn login_handler(
+    State(auth): State<SharedAuth>,
+    AppJson(req): AppJson<CredentialsRequest>,
+) -> Result<AppJson<TokenResponse>, AppError> {
+    let password_hash = auth
+        .accounts
+        .lock()
+        .await
+        .get(&req.name)
+        .map(|a| a.password_hash.clone());
+
+    // アカウントの有無は区別せずに同じエラーを返す。
+    // 応答時間で見分けられないように、無い場合もダミーのハッシュと照合する
+    let ok = match password_hash {
+        Some(hash) => verify_password(req.password, hash).await,
+        None => {
+            verify_password(req.password, DUMMY_PASSWORD_HASH.to_string()).await;
+            false
+        }
+    };
+    if !ok {
+        return Err(AppError::Unauthorized(
+            "invalid name or password".to_string(),
+        ));
+    }
+
+    Ok(AppJson(auth.issue_token(&req.name)?))
+}
+
+/// POST /register, POST /login
+pub fn routes<S>(auth: SharedAuth, config: &ServerConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // パスワードの総当たりと argon2 の負荷を抑えるため IP ごとに回数を制限する
+    let limits = Arc::new(RouteLimits {
+        action: "login",
+        per_ip: RateLimiter::per_minute(config.auth_rate_per_ip),
+        per_account: None,
+        trust_forwarded_for: config.trust_forwarded_for,
+    });
+    Router::new()
+        .route("/register", post(register_handler))
+        .route("/login", post(login_handler))
+        .route_layer(middleware::from_fn_with_state(
+            limits,
+            rate_limit::limit_requests,
+        ))
+        .with_state(auth)
+}
+
+// ===== 認証ミドルウェア =====
+
+/// `Authorization: Bearer <token>` を検証して AuthUser を extensions に入れる
+pub async fn require_auth(
+    State(auth): State<SharedAuth>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or_else(|| AppError::Unauthorized("missing bearer token".to_string()))?;
+
+    let user = auth.verify_token(token.trim())?;
+    req.extensions_mut().insert(user);
+
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argon2::{Algorithm, Params};
+
+    #[test]
+    fn dummy_hash_costs_the_same_as_a_real_one() {
+        let parsed = PasswordHash::new(DUMMY_PASSWORD_HASH).unwrap();
+        let params = Params::try_from(&parsed).unwrap();
+        let default = Params::default();
+        assert_eq!(parsed.algorithm, Algorithm::default().ident());
+        assert_eq!(
+            (params.m_cost(), params.t_cost(), params.p_cost()),
+            (default.m_cost(), default.t_cost(), default.p_cost())
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_account_is_rejected_like_a_wrong_password() {
+        let auth = Arc::new(Auth::new(b"auth-test-secret", Duration::from_secs(60)));
+        let credentials = |name: &str, password: &str| CredentialsRequest {
+            name: name.to_string(),
+            password: password.to_string(),
+        };
+        register_handler(
+            State(auth.clone()),
+            AppJson(credentials("alice", "password123")),
+        )
+        .await
+        .unwrap();
+
+        for (name, password) in [("alice", "wrong-password"), ("bob", "password123")] {
+            let err = login_handler(State(auth.clone()), AppJson(credentials(name, password)))
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(err.code(), "unauthorized");
+            assert_eq!(err.into_message().0, "invalid name or password");
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::error::AppError;
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use battle_api::mime;
+use serde::Serialize;
+use std::convert::Infallible;
+
+// ===== レスポンスの形式（Accept によるコンテントネゴシエーション） =====
+//
+// 大きなバトルの結果やログは JSON だと重いので、MessagePack / CBOR でも返せるようにする。
+// エラーボディはクライアントがいつでも読めるように JSON のまま。
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    MessagePack,
+    Cbor,
+}
+
+impl WireFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            WireFormat::Json => mime::JSON,
+            WireFormat::MessagePack => mime::MSGPACK,
+            WireFormat::Cbor => mime::CBOR,
+        }
+    }
+
+    fn from_mime(mime_type: &str) -> Option<Self> {
+        match mime_type {
+            mime::JSON | "application/*" | "*/*" => Some(WireFormat::Json),
+            mime::MSGPACK | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(WireFormat::MessagePack)
+            }
+            mime::CBOR => Some(WireFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    /// Accept ヘッダから q 値が一番高い対応形式を選ぶ（同じ q なら先に書かれた方）
+    pub fn from_accept(accept: &str) -> Self {
+        let mut best: Option<(f32, WireFormat)> = None;
+        for item in accept.split(',') {
+            let mut parts = item.split(';');
+            let mime_type = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if q <= 0.0 {
+                continue;
+            }
+            if let Some(format) = Self::from_mime(&mime_type) {
+                if best.is_none_or(|(best_q, _)| q > best_q) {
+                    best = Some((q, format));
+                }
+            }
+        }
+        best.map(|(_, format)| format).unwrap_or_default()
+    }
+
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            WireFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            // フィールド名付きの map にして、後からのフィールド追加に耐えられるようにする
+            WireFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            WireFormat::Cbor => {
+                let mut buf = Vec::new();
+                ciborium::into_writer(value, &mut buf).map_err(|e| e.to_string())?;
+                Ok(buf)
+            }
+        }
+    }
+}
+
+/// Accept ヘッダから形式を決める（無い・対応していなければ JSON）
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for WireFormat {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .map(WireFormat::from_accept)
+            .unwrap_or_default())
+    }
+}
+
+/// `AppJson` の代わりに返すと、リクエストの Accept に合わせた形式で返す
+pub struct Negotiated<T>(pub WireFormat, pub T);
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        let Negotiated(format, value) = self;
+        match format.encode(&value) {
+            Ok(body) => (
+                [
+                    (
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(format.content_type()),
+                    ),
+                    (header::VARY, HeaderValue::from_static("accept")),
+                ],
+                body,
+            )
+                .into_response(),
+            Err(e) => {
+                AppError::Internal(format!("failed to encode response: {}", e)).into_response()
+            }
+        }
+    }
+}
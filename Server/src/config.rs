@@ -0,0 +1,213 @@
+use battle_api::{t, GameMode, StatBudget};
+use std::time::Duration;
+
+// ===== サーバ設定（環境変数から読む） =====
+//
+// Kubernetes の Deployment から env で渡す想定。
+// 未設定・不正な値のときはデフォルト値を使う。
+
+/// 待機中に切断したプレイヤーの扱い
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbandonPolicy {
+    /// ロビーから取り除く（バトルに参加させない）
+    Remove,
+    /// NPC として残してバトルに参加させる
+    ConvertToNpc,
+}
+
+impl AbandonPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AbandonPolicy::Remove => "remove",
+            AbandonPolicy::ConvertToNpc => "npc",
+        }
+    }
+}
+
+/// mode ごとのロビーの決まり
+#[derive(Clone, Copy, Debug)]
+pub struct ModeRules {
+    /// この人数に達したら締め切りを待たずに始める
+    pub capacity: usize,
+    /// 1回の参加で入れるキャラクターの数（squad と本人の合計）
+    pub max_squad: usize,
+    /// 足りない分は NPC で埋める
+    pub battle_size: usize,
+    /// 1人目の参加から締め切りまで
+    pub wait: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    /// 1人目の参加からマッチ確定までの待ち時間
+    pub lobby_wait: Duration,
+    pub abandon_policy: AbandonPolicy,
+    /// メモリに残すマッチ履歴の件数
+    pub history_limit: usize,
+    /// トークン署名用の秘密鍵（未設定なら起動ごとにランダム）
+    pub jwt_secret: Option<String>,
+    pub token_ttl: Duration,
+    /// /join の回数制限（1分あたり、0 で無効）
+    pub join_rate_per_ip: u32,
+    pub join_rate_per_account: u32,
+    /// /register と /login の回数制限（IP ごと・1分あたり、0 で無効）
+    pub auth_rate_per_ip: u32,
+    /// /battle と /battle/roster の回数制限（IP ごと・1分あたり、0 で無効）
+    pub instant_rate_per_ip: u32,
+    /// gRPC の RPC 全体の回数制限（接続元 IP ごと・1分あたり、0 で無効）
+    pub grpc_rate_per_ip: u32,
+    /// 全ロビーで同時に待機できる最大人数
+    pub max_pending_joins: usize,
+    /// リクエストボディの上限（バイト）
+    pub max_body_bytes: usize,
+    /// X-Forwarded-For を信用するか（Ingress 越しの場合に true）
+    pub trust_forwarded_for: bool,
+    /// マッチング API（/v1/join など）を公開するか
+    pub enable_matchmaking: bool,
+    /// 即時バトル API（/v1/battle など）を公開するか
+    pub enable_instant: bool,
+    /// gRPC（proto/battle.proto）を待ち受けるポート（0 で無効）
+    pub grpc_port: u16,
+    /// /v1/join のステータスの予算（/v1/version で公開する）
+    pub stat_budget: StatBudget,
+    /// mdns feature でビルドしたとき LAN に広告するか
+    pub advertise_mdns: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            lobby_wait: Duration::from_secs(10),
+            abandon_policy: AbandonPolicy::Remove,
+            history_limit: 100,
+            jwt_secret: None,
+            token_ttl: Duration::from_secs(24 * 60 * 60),
+            join_rate_per_ip: 60,
+            join_rate_per_account: 10,
+            auth_rate_per_ip: 20,
+            instant_rate_per_ip: 30,
+            grpc_rate_per_ip: 120,
+            max_pending_joins: 1000,
+            max_body_bytes: 16 * 1024,
+            trust_forwarded_for: false,
+            enable_matchmaking: true,
+            enable_instant: true,
+            grpc_port: 50051,
+            stat_budget: StatBudget::default(),
+            advertise_mdns: true,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// classic は mode 導入前と同じ（BATTLE_LOBBY_WAIT_SECS / BATTLE_MAX_PENDING_JOINS）
+    pub fn mode_rules(&self, mode: GameMode) -> ModeRules {
+        let (capacity, max_squad, battle_size, wait) = match mode {
+            // Unknown は /join の入力チェックで断るので来ないが、来ても classic と同じにする
+            GameMode::Classic | GameMode::Unknown => {
+                (self.max_pending_joins, 5, 100, self.lobby_wait)
+            }
+            GameMode::Skirmish => (10, 3, 20, self.lobby_wait),
+            // 相手を待つ時間を長めにとる
+            GameMode::Duel => (2, 1, 2, self.lobby_wait * 3),
+        };
+        let capacity = capacity.min(self.max_pending_joins).max(1);
+        ModeRules {
+            capacity,
+            max_squad: max_squad.min(capacity),
+            battle_size,
+            wait,
+        }
+    }
+
+    /// BATTLE_LOBBY_WAIT_SECS / BATTLE_ABANDON_POLICY / BATTLE_HISTORY_LIMIT /
+    /// BATTLE_JWT_SECRET / BATTLE_TOKEN_TTL_SECS /
+    /// BATTLE_JOIN_RATE_PER_IP / BATTLE_JOIN_RATE_PER_ACCOUNT / BATTLE_AUTH_RATE_PER_IP /
+    /// BATTLE_INSTANT_RATE_PER_IP / BATTLE_GRPC_RATE_PER_IP /
+    /// BATTLE_MAX_PENDING_JOINS /
+    /// BATTLE_MAX_BODY_BYTES / BATTLE_TRUST_FORWARDED_FOR /
+    /// BATTLE_ENABLE_MATCHMAKING / BATTLE_ENABLE_INSTANT / BATTLE_GRPC_PORT /
+    /// BATTLE_STAT_POINTS / BATTLE_MDNS
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        let lobby_wait = env_parse("BATTLE_LOBBY_WAIT_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(default.lobby_wait);
+
+        let abandon_policy = match std::env::var("BATTLE_ABANDON_POLICY").as_deref() {
+            Ok("remove") => AbandonPolicy::Remove,
+            Ok("npc") => AbandonPolicy::ConvertToNpc,
+            Ok(other) => {
+                eprintln!(
+                    "{}",
+                    t!(
+                        "config-unknown-abandon-policy",
+                        value = other,
+                        default = default.abandon_policy.as_str()
+                    )
+                );
+                default.abandon_policy
+            }
+            Err(_) => default.abandon_policy,
+        };
+
+        let history_limit = env_parse("BATTLE_HISTORY_LIMIT").unwrap_or(default.history_limit);
+
+        let jwt_secret = std::env::var("BATTLE_JWT_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        let token_ttl = env_parse("BATTLE_TOKEN_TTL_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(default.token_ttl);
+
+        let stat_budget = StatBudget {
+            points: env_parse("BATTLE_STAT_POINTS").unwrap_or(default.stat_budget.points),
+            ..default.stat_budget
+        };
+
+        Self {
+            lobby_wait,
+            abandon_policy,
+            history_limit,
+            jwt_secret,
+            token_ttl,
+            join_rate_per_ip: env_parse("BATTLE_JOIN_RATE_PER_IP")
+                .unwrap_or(default.join_rate_per_ip),
+            join_rate_per_account: env_parse("BATTLE_JOIN_RATE_PER_ACCOUNT")
+                .unwrap_or(default.join_rate_per_account),
+            auth_rate_per_ip: env_parse("BATTLE_AUTH_RATE_PER_IP")
+                .unwrap_or(default.auth_rate_per_ip),
+            instant_rate_per_ip: env_parse("BATTLE_INSTANT_RATE_PER_IP")
+                .unwrap_or(default.instant_rate_per_ip),
+            grpc_rate_per_ip: env_parse("BATTLE_GRPC_RATE_PER_IP")
+                .unwrap_or(default.grpc_rate_per_ip),
+            max_pending_joins: env_parse("BATTLE_MAX_PENDING_JOINS")
+                .unwrap_or(default.max_pending_joins),
+            max_body_bytes: env_parse("BATTLE_MAX_BODY_BYTES").unwrap_or(default.max_body_bytes),
+            trust_forwarded_for: env_parse("BATTLE_TRUST_FORWARDED_FOR")
+                .unwrap_or(default.trust_forwarded_for),
+            enable_matchmaking: env_parse("BATTLE_ENABLE_MATCHMAKING")
+                .unwrap_or(default.enable_matchmaking),
+            enable_instant: env_parse("BATTLE_ENABLE_INSTANT").unwrap_or(default.enable_instant),
+            grpc_port: env_parse("BATTLE_GRPC_PORT").unwrap_or(default.grpc_port),
+            stat_budget,
+            advertise_mdns: env_parse("BATTLE_MDNS").unwrap_or(default.advertise_mdns),
+        }
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    let raw = std::env::var(key).ok()?;
+    match raw.trim().parse() {
+        Ok(v) => Some(v),
+        Err(_) => {
+            eprintln!(
+                "{}",
+                t!("config-invalid-value", key = key, value = raw.as_str())
+            );
+            None
+        }
+    }
+}
@@ -0,0 +1,48 @@
+// ===== mDNS による LAN 内の広告（mdns feature） =====
+//
+// 開発用のクラスタやローカルのサーバをクライアントの「Discover」で見つけられるようにする。
+// インスタンス名は BATTLE_MDNS_NAME（省略時は HOSTNAME、それも無ければ battle-server）。
+
+use battle_api::{mdns, t, PROTOCOL_VERSION};
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+
+/// HTTP ポートを広告する。戻り値の ServiceDaemon を drop すると広告も止まる
+pub fn advertise(http_port: u16) -> Result<ServiceDaemon, mdns_sd::Error> {
+    let instance = std::env::var("BATTLE_MDNS_NAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "battle-server".to_string());
+    // ホスト名に使えない文字は - にする
+    let host: String = instance
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+
+    let protocol = PROTOCOL_VERSION.to_string();
+    let properties = [
+        (mdns::TXT_VERSION, env!("CARGO_PKG_VERSION")),
+        (mdns::TXT_PROTOCOL, protocol.as_str()),
+    ];
+    let service = ServiceInfo::new(
+        mdns::SERVICE_TYPE,
+        &instance,
+        &format!("{}.local.", host),
+        "",
+        http_port,
+        &properties[..],
+    )?
+    .enable_addr_auto();
+
+    let daemon = ServiceDaemon::new()?;
+    daemon.register(service)?;
+    println!(
+        "{}",
+        t!(
+            "server-mdns-advertising",
+            instance = instance.as_str(),
+            service = mdns::SERVICE_TYPE
+        )
+    );
+    Ok(daemon)
+}
@@ -0,0 +1,10 @@
+// ===== バトルエンジン =====
+//
+// クライアントが結果を再計算して確かめられるように、本体は battle_api::engine にある。
+// ここではサーバだけが使う値を足して再公開する。
+
+pub use battle_api::engine::*;
+
+/// ロビーの NPC と同じ範囲（lobby の finalize_match）
+pub const NPC_HP: StatRange = StatRange::new(80, 119);
+pub const NPC_ATK: StatRange = StatRange::new(5, 19);
@@ -0,0 +1,226 @@
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use battle_api::t;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+
+// エラーボディの形式は battle_api::ErrorBody（クライアントと共通）
+pub use battle_api::ErrorBody;
+
+// ===== サーバのエラー型 =====
+
+#[derive(Debug)]
+pub enum AppError {
+    /// ボディが JSON として読めない / 型が合わない
+    Json(JsonRejection),
+    /// JSON としては正しいが値が不正
+    InvalidRequest {
+        message: String,
+        details: Option<Value>,
+    },
+    /// 認証されていない（トークンが無い / 不正 / 期限切れ）
+    Unauthorized(String),
+    /// 認証済みだが許可されていない操作
+    Forbidden(String),
+    /// 指定されたリソースが存在しない
+    NotFound(String),
+    /// 既存の状態と衝突する（チケットの重複など）
+    Conflict(String),
+    /// 受け付けない Content-Type
+    UnsupportedMediaType(String),
+    /// 回数制限・同時待機数の上限に達した
+    TooManyRequests {
+        message: String,
+        retry_after: Duration,
+    },
+    /// クライアントのプロトコルが古すぎる
+    ClientTooOld { client: u32, min: u32 },
+    /// 参加待ちがプレイヤー自身によって取り消された
+    JoinCancelled,
+    /// マッチ確定前に結果の送信口が失われた
+    MatchAborted,
+    /// 想定外のサーバ内部エラー
+    Internal(String),
+}
+
+impl AppError {
+    pub fn invalid(message: impl Into<String>) -> Self {
+        AppError::InvalidRequest {
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// 不正なフィールド名を details に載せる
+    pub fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        AppError::InvalidRequest {
+            message: message.into(),
+            details: Some(serde_json::json!({ "field": field })),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            AppError::Json(rejection) => rejection.status(),
+            AppError::InvalidRequest { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) | AppError::JoinCancelled => StatusCode::CONFLICT,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::ClientTooOld { .. } => StatusCode::UPGRADE_REQUIRED,
+            AppError::MatchAborted => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// ErrorBody::code に入る値（gRPC でもメッセージの先頭に付ける）
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Json(JsonRejection::JsonDataError(_)) => "invalid_body",
+            AppError::Json(JsonRejection::JsonSyntaxError(_)) => "malformed_json",
+            AppError::Json(JsonRejection::MissingJsonContentType(_)) => "unsupported_media_type",
+            AppError::Json(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                "payload_too_large"
+            }
+            AppError::Json(_) => "unreadable_body",
+            AppError::InvalidRequest { .. } => "invalid_request",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::TooManyRequests { .. } => "rate_limited",
+            AppError::UnsupportedMediaType(_) => "unsupported_media_type",
+            AppError::ClientTooOld { .. } => "client_too_old",
+            AppError::JoinCancelled => "join_cancelled",
+            AppError::MatchAborted => "match_aborted",
+            AppError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// クライアントに返すメッセージと details（内部エラーの中身はログにだけ出す）
+    pub fn into_message(self) -> (String, Option<Value>) {
+        match self {
+            AppError::Json(rejection) => {
+                let details = json_error_details(&rejection);
+                (rejection.body_text(), details)
+            }
+            AppError::InvalidRequest { message, details } => (message, details),
+            AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::NotFound(msg)
+            | AppError::Conflict(msg)
+            | AppError::UnsupportedMediaType(msg) => (msg, None),
+            AppError::TooManyRequests {
+                message,
+                retry_after,
+            } => (
+                message,
+                Some(serde_json::json!({ "retry_after_secs": retry_after.as_secs_f64() })),
+            ),
+            AppError::ClientTooOld { client, min } => (
+                format!(
+                    "client protocol {} is no longer supported, please update the client",
+                    client
+                ),
+                Some(serde_json::json!({
+                    "client_protocol_version": client,
+                    "min_protocol_version": min,
+                    "protocol_version": battle_api::PROTOCOL_VERSION,
+                })),
+            ),
+            AppError::JoinCancelled => ("join was cancelled by the player".to_string(), None),
+            AppError::MatchAborted => (
+                "match was aborted before a result was produced".to_string(),
+                None,
+            ),
+            AppError::Internal(msg) => {
+                // 内部情報はログにだけ出してクライアントには返さない
+                eprintln!("{}", t!("error-internal", error = msg.as_str()));
+                ("internal server error".to_string(), None)
+            }
+        }
+    }
+}
+
+/// 読めなかった JSON の位置（line / column）と、型が合わなかったフィールドのパス
+fn json_error_details(rejection: &JsonRejection) -> Option<Value> {
+    let mut source = std::error::Error::source(rejection);
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<serde_path_to_error::Error<serde_json::Error>>() {
+            let inner = err.inner();
+            let mut details = serde_json::json!({
+                "line": inner.line(),
+                "column": inner.column(),
+            });
+            let path = err.path().to_string();
+            if path != "." {
+                details["field"] = Value::String(path);
+            }
+            return Some(details);
+        }
+        source = err.source();
+    }
+    None
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        AppError::Json(rejection)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+        let extra_header = match &self {
+            AppError::Unauthorized(_) => Some((
+                header::WWW_AUTHENTICATE,
+                header::HeaderValue::from_static("Bearer"),
+            )),
+            AppError::TooManyRequests { retry_after, .. } => {
+                // Retry-After は秒単位（切り上げ、最低1秒）
+                let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                Some((header::RETRY_AFTER, header::HeaderValue::from(secs.max(1))))
+            }
+            _ => None,
+        };
+
+        let (message, details) = self.into_message();
+
+        let mut response = (
+            status,
+            Json(ErrorBody {
+                code: code.to_string(),
+                message,
+                details,
+            }),
+        )
+            .into_response();
+
+        if let Some((name, value)) = extra_header {
+            response.headers_mut().insert(name, value);
+        }
+        response
+    }
+}
+
+// ===== JSON エクストラクタ =====
+
+/// `axum::Json` と同じだが、失敗時に AppError（JSON エラーボディ）を返す
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct AppJson<T>(pub T);
+
+impl<T: Serialize> IntoResponse for AppJson<T> {
+    fn into_response(self) -> Response {
+        Json(self.0).into_response()
+    }
+}
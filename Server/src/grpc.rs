@@ -0,0 +1,447 @@
+// ===== gRPC サービス（proto/battle.proto） =====
+//
+// HTTP の /v1 と同じ LobbyManager・バトルエンジンを使う。
+// 無効にしているモード（マッチング / 即時バトル）の RPC は UNIMPLEMENTED を返す。
+
+use crate::auth::{AuthUser, SharedAuth};
+use crate::config::ServerConfig;
+use crate::error::AppError;
+use crate::instant;
+use crate::lobby::{LeaderboardEntry, LobbyEvent, SharedLobby};
+use crate::rate_limit::{self, RateLimiter};
+use battle_api::{
+    BattleRequest, BattleResult, CharacterClass, ClientCharacterInput, ClientCharacterResult,
+    GameMode, JoinRequest, JoinResponse, LobbyInfo, SquadMember, StatRange, Targeting,
+};
+use std::sync::Arc;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio::time::Instant;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::service::{interceptor::InterceptedService, Interceptor};
+use tonic::{Code, Request, Response, Status};
+
+pub mod pb {
+    tonic::include_proto!("battle.v1");
+}
+
+use pb::battle_service_server::{BattleService, BattleServiceServer};
+use pb::match_update::Update;
+
+/// GetLeaderboard の limit 省略時と上限
+const DEFAULT_LEADERBOARD_LIMIT: usize = 10;
+const MAX_LEADERBOARD_LIMIT: usize = 100;
+
+pub struct BattleGrpc {
+    /// マッチングが無効なら None
+    lobby: Option<SharedLobby>,
+    auth: SharedAuth,
+    enable_instant: bool,
+}
+
+/// tonic の Server::add_service に渡すサービスを作る
+///
+/// メッセージの大きさは HTTP のボディと同じ上限にし、接続元 IP ごとに回数を制限する
+pub fn service(
+    lobby: Option<SharedLobby>,
+    auth: SharedAuth,
+    config: &ServerConfig,
+) -> InterceptedService<BattleServiceServer<BattleGrpc>, RateLimit> {
+    let server = BattleServiceServer::new(BattleGrpc {
+        lobby,
+        auth,
+        enable_instant: config.enable_instant,
+    })
+    .max_decoding_message_size(config.max_body_bytes);
+    let limit = RateLimit {
+        per_ip: RateLimiter::per_minute(config.grpc_rate_per_ip).map(Arc::new),
+        trust_forwarded_for: config.trust_forwarded_for,
+    };
+    InterceptedService::new(server, limit)
+}
+
+// ===== 回数制限 =====
+
+/// すべての RPC を接続元 IP ごとに数える（ストリームは開始時の1回）
+#[derive(Clone)]
+pub struct RateLimit {
+    per_ip: Option<Arc<RateLimiter>>,
+    trust_forwarded_for: bool,
+}
+
+impl Interceptor for RateLimit {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(limiter) = &self.per_ip else {
+            return Ok(request);
+        };
+        let forwarded_for = request
+            .metadata()
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok());
+        let ip = rate_limit::client_ip(
+            forwarded_for,
+            request.remote_addr(),
+            self.trust_forwarded_for,
+        );
+        limiter.check(&ip).map_err(|retry_after| {
+            to_status(AppError::TooManyRequests {
+                message: "too many requests from this address".to_string(),
+                retry_after,
+            })
+        })?;
+        Ok(request)
+    }
+}
+
+// ===== エラーの変換 =====
+
+/// HTTP のステータスに対応する gRPC のコードにする（メッセージは "<code>: <message>"）
+fn to_status(err: AppError) -> Status {
+    let code = match &err {
+        AppError::Json(_) | AppError::InvalidRequest { .. } => Code::InvalidArgument,
+        AppError::Unauthorized(_) => Code::Unauthenticated,
+        AppError::Forbidden(_) => Code::PermissionDenied,
+        AppError::NotFound(_) => Code::NotFound,
+        AppError::Conflict(_) => Code::AlreadyExists,
+        AppError::TooManyRequests { .. } => Code::ResourceExhausted,
+        AppError::UnsupportedMediaType(_) | AppError::ClientTooOld { .. } => {
+            Code::FailedPrecondition
+        }
+        AppError::JoinCancelled => Code::Cancelled,
+        AppError::MatchAborted => Code::Unavailable,
+        AppError::Internal(_) => Code::Internal,
+    };
+    let error_code = err.code();
+    let (message, _) = err.into_message();
+    Status::new(code, format!("{}: {}", error_code, message))
+}
+
+fn matchmaking_disabled() -> Status {
+    Status::unimplemented("matchmaking is disabled on this server")
+}
+
+/// メタデータの `authorization: Bearer <token>` を検証する
+fn authenticate<T>(auth: &SharedAuth, request: &Request<T>) -> Result<AuthUser, AppError> {
+    let token = request
+        .metadata()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or_else(|| AppError::Unauthorized("missing bearer token".to_string()))?;
+    auth.verify_token(token.trim())
+}
+
+// ===== proto との変換 =====
+
+// 知らない enum の値は 0 番（fighter / random / classic）として扱われる
+
+impl From<pb::CharacterClass> for CharacterClass {
+    fn from(class: pb::CharacterClass) -> Self {
+        match class {
+            pb::CharacterClass::Fighter => CharacterClass::Fighter,
+            pb::CharacterClass::Tank => CharacterClass::Tank,
+            pb::CharacterClass::Striker => CharacterClass::Striker,
+        }
+    }
+}
+
+impl From<pb::Targeting> for Targeting {
+    fn from(targeting: pb::Targeting) -> Self {
+        match targeting {
+            pb::Targeting::Random => Targeting::Random,
+            pb::Targeting::Weakest => Targeting::Weakest,
+            pb::Targeting::Strongest => Targeting::Strongest,
+        }
+    }
+}
+
+impl From<pb::SquadMember> for SquadMember {
+    fn from(member: pb::SquadMember) -> Self {
+        Self {
+            class: member.class().into(),
+            targeting: member.targeting().into(),
+            name: member.name,
+            hp: member.hp,
+            atk: member.atk,
+        }
+    }
+}
+
+impl From<pb::GameMode> for GameMode {
+    fn from(mode: pb::GameMode) -> Self {
+        match mode {
+            pb::GameMode::Classic => GameMode::Classic,
+            pb::GameMode::Skirmish => GameMode::Skirmish,
+            pb::GameMode::Duel => GameMode::Duel,
+        }
+    }
+}
+
+impl From<pb::JoinRequest> for JoinRequest {
+    fn from(req: pb::JoinRequest) -> Self {
+        Self {
+            class: req.class().into(),
+            targeting: req.targeting().into(),
+            mode: req.mode().into(),
+            name: req.name,
+            hp: req.hp,
+            atk: req.atk,
+            ticket: req.ticket,
+            lobby: req.lobby,
+            squad: req.squad.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<GameMode> for pb::GameMode {
+    fn from(mode: GameMode) -> Self {
+        match mode {
+            // サーバが Unknown のロビーを開くことはない
+            GameMode::Classic | GameMode::Unknown => pb::GameMode::Classic,
+            GameMode::Skirmish => pb::GameMode::Skirmish,
+            GameMode::Duel => pb::GameMode::Duel,
+        }
+    }
+}
+
+impl From<LobbyInfo> for pb::Lobby {
+    fn from(info: LobbyInfo) -> Self {
+        Self {
+            id: info.id,
+            mode: pb::GameMode::from(info.mode).into(),
+            players: info.players as u32,
+            capacity: info.capacity as u32,
+            battle_size: info.battle_size as u32,
+            seconds_left: info.seconds_left as u32,
+            max_squad: info.max_squad as u32,
+        }
+    }
+}
+
+impl From<JoinResponse> for pb::JoinResponse {
+    fn from(res: JoinResponse) -> Self {
+        Self {
+            name: res.name,
+            rank: res.rank as u32,
+            final_hp: res.final_hp,
+            is_winner: res.is_winner,
+            match_id: res.match_id,
+            squad: res.squad.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<ClientCharacterResult> for pb::CharacterResult {
+    fn from(r: ClientCharacterResult) -> Self {
+        Self {
+            name: r.name,
+            rank: r.rank as u32,
+            final_hp: r.final_hp,
+            is_winner: r.is_winner,
+        }
+    }
+}
+
+impl From<pb::StatRange> for StatRange {
+    fn from(range: pb::StatRange) -> Self {
+        StatRange::new(range.min, range.max)
+    }
+}
+
+impl From<pb::BattleRequest> for BattleRequest {
+    fn from(req: pb::BattleRequest) -> Self {
+        Self {
+            characters: req
+                .characters
+                .into_iter()
+                .map(|c| ClientCharacterInput {
+                    targeting: c.targeting().into(),
+                    name: c.name,
+                    hp: c.hp,
+                    atk: c.atk,
+                })
+                .collect(),
+            total_chars: req.total_chars.map(|n| n as usize),
+            npc_hp: req.npc_hp.map(StatRange::from),
+            npc_atk: req.npc_atk.map(StatRange::from),
+            seed: req.seed,
+            return_full_standings: req.return_full_standings,
+        }
+    }
+}
+
+impl From<BattleResult> for pb::BattleResult {
+    fn from(res: BattleResult) -> Self {
+        Self {
+            total_chars: res.total_chars as u32,
+            seed: res.seed,
+            client_results: res.client_results.into_iter().map(Into::into).collect(),
+            standings: res
+                .standings
+                .unwrap_or_default()
+                .into_iter()
+                .map(|s| pb::Standing {
+                    rank: s.rank as u32,
+                    name: s.name,
+                    final_hp: s.final_hp,
+                    is_client: s.is_client,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<LeaderboardEntry> for pb::LeaderboardEntry {
+    fn from(entry: LeaderboardEntry) -> Self {
+        Self {
+            name: entry.name,
+            matches: entry.matches,
+            wins: entry.wins,
+            best_rank: entry.best_rank as u32,
+        }
+    }
+}
+
+/// ロビーのイベントのうち ticket（lobby に入っている）に関係するものを MatchUpdate にする
+/// （2つ目の値は、これを送ったらストリームを閉じるか）
+fn match_update(event: LobbyEvent, ticket: &str, lobby_id: u64) -> Option<(Update, bool)> {
+    match event {
+        // 他のロビーの Waiting は送らない
+        LobbyEvent::Waiting {
+            lobby,
+            players,
+            deadline,
+        } if lobby == lobby_id => {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let seconds_left = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+            Some((
+                Update::Waiting(pb::Waiting {
+                    players: players as u32,
+                    seconds_left: seconds_left as u32,
+                    lobby,
+                }),
+                false,
+            ))
+        }
+        LobbyEvent::Started { tickets } if tickets.iter().any(|t| t == ticket) => Some((
+            Update::Started(pb::Started {
+                players: tickets.len() as u32,
+            }),
+            false,
+        )),
+        LobbyEvent::Finished { results } => results
+            .into_iter()
+            .find(|(t, _)| t == ticket)
+            .map(|(_, result)| (Update::Finished(result.into()), true)),
+        LobbyEvent::Cancelled { ticket: t } if t == ticket => {
+            Some((Update::Cancelled(pb::Cancelled {}), true))
+        }
+        _ => None,
+    }
+}
+
+// ===== RPC =====
+
+#[tonic::async_trait]
+impl BattleService for BattleGrpc {
+    async fn join(
+        &self,
+        request: Request<pb::JoinRequest>,
+    ) -> Result<Response<pb::JoinResponse>, Status> {
+        let lobby = self.lobby.as_ref().ok_or_else(matchmaking_disabled)?;
+        let user = authenticate(&self.auth, &request).map_err(to_status)?;
+        // 呼び出しが切られると結果の受信側が drop され、HTTP と同じく切断扱いになる
+        let result = lobby
+            .join(&user, request.into_inner().into())
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(result.into()))
+    }
+
+    async fn battle(
+        &self,
+        request: Request<pb::BattleRequest>,
+    ) -> Result<Response<pb::BattleResult>, Status> {
+        if !self.enable_instant {
+            return Err(Status::unimplemented(
+                "instant battles are disabled on this server",
+            ));
+        }
+        let result = instant::run_battle_request(request.into_inner().into())
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(result.into()))
+    }
+
+    type WatchMatchStream = ReceiverStream<Result<pb::MatchUpdate, Status>>;
+
+    async fn watch_match(
+        &self,
+        request: Request<pb::WatchMatchRequest>,
+    ) -> Result<Response<Self::WatchMatchStream>, Status> {
+        let lobby = self.lobby.as_ref().ok_or_else(matchmaking_disabled)?;
+        let user = authenticate(&self.auth, &request).map_err(to_status)?;
+        let ticket = request.into_inner().ticket;
+        let (current, mut events) = lobby.watch(&user, &ticket).await.map_err(to_status)?;
+        // watch は入っているロビーの Waiting を返す
+        let lobby_id = current.lobby().unwrap_or_default();
+
+        let (tx, rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut next = Some(current);
+            loop {
+                let event = match next.take() {
+                    Some(event) => event,
+                    None => match events.recv().await {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(_)) => {
+                            // 取りこぼした中に結果があるかもしれないので打ち切る
+                            let _ = tx
+                                .send(Err(Status::data_loss("watcher fell behind the lobby")))
+                                .await;
+                            break;
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+                let Some((update, done)) = match_update(event, &ticket, lobby_id) else {
+                    continue;
+                };
+                let update = pb::MatchUpdate {
+                    update: Some(update),
+                };
+                // 観戦側が切断した
+                if tx.send(Ok(update)).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn list_lobbies(
+        &self,
+        _request: Request<pb::ListLobbiesRequest>,
+    ) -> Result<Response<pb::LobbyList>, Status> {
+        let lobby = self.lobby.as_ref().ok_or_else(matchmaking_disabled)?;
+        let lobbies = lobby.lobbies().await;
+        Ok(Response::new(pb::LobbyList {
+            lobbies: lobbies.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_leaderboard(
+        &self,
+        request: Request<pb::LeaderboardRequest>,
+    ) -> Result<Response<pb::Leaderboard>, Status> {
+        let lobby = self.lobby.as_ref().ok_or_else(matchmaking_disabled)?;
+        let limit = match request.into_inner().limit as usize {
+            0 => DEFAULT_LEADERBOARD_LIMIT,
+            n => n.min(MAX_LEADERBOARD_LIMIT),
+        };
+        let entries = lobby.leaderboard(limit).await;
+        Ok(Response::new(pb::Leaderboard {
+            entries: entries.into_iter().map(Into::into).collect(),
+        }))
+    }
+}
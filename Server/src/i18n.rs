@@ -0,0 +1,16 @@
+// ===== 表示メッセージのカタログ =====
+//
+// サーバのログと hello_world の出力（locales/*.ftl）。
+// 言語は BATTLE_LANG（無ければ LANG など）で選ぶ。API のエラーメッセージは英語のまま。
+
+use battle_api::i18n::{self, Catalog, Locale};
+
+pub const CATALOG: Catalog = &[
+    (Locale::En, include_str!("../locales/en.ftl")),
+    (Locale::Ja, include_str!("../locales/ja.ftl")),
+];
+
+/// locale を省略したら環境変数から決める
+pub fn init(locale: Option<Locale>) {
+    i18n::init(CATALOG, locale.unwrap_or_else(Locale::from_env));
+}
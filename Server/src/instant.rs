@@ -0,0 +1,315 @@
+// ===== 即時バトル API =====
+//
+// POST /battle と POST /battle/roster。ロビーを使わずその場で1戦して結果を返す。
+
+use crate::codec::{Negotiated, WireFormat};
+use crate::config::ServerConfig;
+use crate::engine::{self, BattleOutcome, Fighter, StatRange, Targeting};
+use crate::error::{AppError, AppJson};
+use crate::rate_limit::{self, RateLimiter, RouteLimits};
+use crate::roster::{Roster, RosterFormat};
+use axum::{
+    body::Bytes,
+    http::{header::CONTENT_TYPE, HeaderMap},
+    middleware,
+    routing::post,
+    Router,
+};
+use battle_api::{
+    BattleRequest, BattleResult, ClientCharacterInput, ClientCharacterResult, Standing, MAX_STAT,
+};
+use rand::Rng;
+use std::sync::Arc;
+
+// ===== 入力チェック =====
+
+/// 省略時のステータス範囲（クライアント・NPC 共通）
+const DEFAULT_HP: StatRange = StatRange::new(50, 100);
+const DEFAULT_ATK: StatRange = StatRange::new(20, 40);
+const DEFAULT_TOTAL_CHARS: usize = 100;
+/// 1回のバトルの最大人数（/battle と /battle/roster 共通）
+const MAX_BATTLE_SIZE: usize = 10_000;
+
+fn validate_battle(req: &BattleRequest) -> Result<(), AppError> {
+    for (i, c) in req.characters.iter().enumerate() {
+        let field = |name: &str| format!("characters[{}].{}", i, name);
+        if c.name.trim().is_empty() {
+            return Err(AppError::invalid_field(
+                &field("name"),
+                "name must not be empty",
+            ));
+        }
+        if c.hp.is_some_and(|hp| !(1..=MAX_STAT).contains(&hp)) {
+            return Err(AppError::invalid_field(
+                &field("hp"),
+                format!("hp must be 1-{}", MAX_STAT),
+            ));
+        }
+        if c.atk.is_some_and(|atk| !(0..=MAX_STAT).contains(&atk)) {
+            return Err(AppError::invalid_field(
+                &field("atk"),
+                format!("atk must be 0-{}", MAX_STAT),
+            ));
+        }
+        if c.targeting == Targeting::Unknown {
+            return Err(AppError::invalid_field(
+                &field("targeting"),
+                format!(
+                    "targeting must be one of {}",
+                    Targeting::ALL.map(Targeting::as_str).join(", ")
+                ),
+            ));
+        }
+    }
+
+    let total = req.total_chars.unwrap_or(DEFAULT_TOTAL_CHARS);
+    if total == 0 || total > MAX_BATTLE_SIZE {
+        return Err(AppError::invalid_field(
+            "total_chars",
+            format!("total_chars must be 1-{}", MAX_BATTLE_SIZE),
+        ));
+    }
+    if req.characters.len() > total {
+        return Err(AppError::invalid_field(
+            "total_chars",
+            "total_chars must not be smaller than the number of characters",
+        ));
+    }
+
+    // NPC は atk 0 だと数合わせにもならないので 1 以上にする
+    let ranges = [("npc_hp", req.npc_hp), ("npc_atk", req.npc_atk)];
+    for (field, range) in ranges {
+        let Some(range) = range else { continue };
+        if !range.is_valid() {
+            return Err(AppError::invalid_field(field, "min must not exceed max"));
+        }
+        if range.min < 1 {
+            return Err(AppError::invalid_field(field, "min must be at least 1"));
+        }
+        if range.max > MAX_STAT {
+            return Err(AppError::invalid_field(
+                field,
+                format!("max must be at most {}", MAX_STAT),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// ===== NPC 生成（バトル本体は engine） =====
+fn random_character<R: Rng>(
+    rng: &mut R,
+    name_prefix: &str,
+    index: usize,
+    hp: StatRange,
+    atk: StatRange,
+) -> Fighter {
+    Fighter {
+        name: format!("{}{}", name_prefix, index),
+        hp: hp.sample(rng),
+        atk: atk.sample(rng),
+        is_client: false,
+        targeting: Targeting::Random,
+    }
+}
+
+fn to_result(outcome: &BattleOutcome, seed: u64, full_standings: bool) -> BattleResult {
+    let ranks = outcome.ranks();
+
+    let client_results = outcome
+        .fighters
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.is_client)
+        .map(|(i, c)| ClientCharacterResult {
+            name: c.name.clone(),
+            rank: ranks[i],
+            final_hp: c.hp,
+            is_winner: ranks[i] == 1,
+        })
+        .collect();
+
+    let standings = full_standings.then(|| {
+        outcome
+            .standings()
+            .map(|i| Standing {
+                rank: ranks[i],
+                name: outcome.fighters[i].name.clone(),
+                final_hp: outcome.fighters[i].hp,
+                is_client: outcome.fighters[i].is_client,
+            })
+            .collect()
+    });
+
+    BattleResult {
+        total_chars: outcome.fighters.len(),
+        seed,
+        client_results,
+        standings,
+    }
+}
+
+// ===== POST /battle =====
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/v1/battle",
+    tag = "instant",
+    request_body = BattleRequest,
+    responses(
+        (status = 200, description = "battle finished", content(
+            ("application/json" = BattleResult),
+            ("application/msgpack" = BattleResult),
+            ("application/cbor" = BattleResult),
+        )),
+        (status = 422, description = "invalid request", body = ErrorBody),
+    )
+))]
+async fn battle_handler(
+    format: WireFormat,
+    AppJson(req): AppJson<BattleRequest>,
+) -> Result<Negotiated<BattleResult>, AppError> {
+    Ok(Negotiated(format, run_battle_request(req).await?))
+}
+
+/// 入力チェックから NPC の補充・バトルまで（HTTP と gRPC で共通）
+///
+/// 最大 MAX_BATTLE_SIZE 人のバトルは数十 ms かかるので blocking スレッドで回す
+pub async fn run_battle_request(req: BattleRequest) -> Result<BattleResult, AppError> {
+    validate_battle(&req)?;
+    tokio::task::spawn_blocking(move || battle(req))
+        .await
+        .map_err(|e| AppError::Internal(format!("battle task failed: {}", e)))
+}
+
+fn battle(req: BattleRequest) -> BattleResult {
+    let seed = req.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = engine::battle_rng(seed);
+
+    let total_chars = req.total_chars.unwrap_or(DEFAULT_TOTAL_CHARS);
+    let npc_hp = req.npc_hp.unwrap_or(DEFAULT_HP);
+    let npc_atk = req.npc_atk.unwrap_or(DEFAULT_ATK);
+
+    let mut chars: Vec<Fighter> = req
+        .characters
+        .into_iter()
+        .map(|c| {
+            let hp = c.hp.unwrap_or_else(|| DEFAULT_HP.sample(&mut rng));
+            let atk = c.atk.unwrap_or_else(|| DEFAULT_ATK.sample(&mut rng));
+            Fighter {
+                name: c.name,
+                hp,
+                atk,
+                is_client: true,
+                targeting: c.targeting,
+            }
+        })
+        .collect();
+
+    let need = total_chars - chars.len();
+    for i in 0..need {
+        chars.push(random_character(&mut rng, "NPC_", i, npc_hp, npc_atk));
+    }
+
+    let outcome = engine::run_battle(chars, &mut rng);
+    to_result(&outcome, seed, req.return_full_standings)
+}
+
+// ===== POST /battle/roster（NPC で埋めない） =====
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/v1/battle/roster",
+    tag = "instant",
+    request_body(
+        content = String,
+        description = "roster file (version 1 or 2)",
+        content_type = "application/toml"
+    ),
+    responses(
+        (status = 200, description = "battle finished", content(
+            ("application/json" = BattleResult),
+            ("application/msgpack" = BattleResult),
+            ("application/cbor" = BattleResult),
+        )),
+        (status = 415, description = "not JSON or TOML", body = ErrorBody),
+        (status = 422, description = "invalid roster", body = ErrorBody),
+    )
+))]
+async fn roster_battle_handler(
+    wire: WireFormat,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Negotiated<BattleResult>, AppError> {
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    let format = RosterFormat::from_content_type(content_type).ok_or_else(|| {
+        AppError::UnsupportedMediaType(
+            "roster must be sent as application/json or application/toml".to_string(),
+        )
+    })?;
+    let text = std::str::from_utf8(&body)
+        .map_err(|_| AppError::invalid("roster body must be valid UTF-8"))?;
+
+    let roster = Roster::parse(text, format)?;
+    if roster.characters.len() < 2 {
+        return Err(AppError::invalid_field(
+            "characters",
+            "a battle needs at least 2 characters",
+        ));
+    }
+    if roster.characters.len() > MAX_BATTLE_SIZE {
+        return Err(AppError::invalid_field(
+            "characters",
+            format!(
+                "a roster battle is limited to {} characters",
+                MAX_BATTLE_SIZE
+            ),
+        ));
+    }
+
+    // クラス補正をかけた値で、NPC を足さずに /battle と同じ道を通す
+    let total_chars = roster.characters.len();
+    let characters = roster
+        .characters
+        .into_iter()
+        .map(|c| {
+            let (hp, atk) = c.class.derive_stats(c.hp, c.atk);
+            ClientCharacterInput {
+                name: c.name,
+                hp: Some(hp),
+                atk: Some(atk),
+                targeting: c.targeting,
+            }
+        })
+        .collect();
+    let req = BattleRequest {
+        characters,
+        total_chars: Some(total_chars),
+        npc_hp: None,
+        npc_atk: None,
+        seed: None,
+        return_full_standings: true,
+    };
+    Ok(Negotiated(wire, run_battle_request(req).await?))
+}
+
+// ===== ルーター =====
+
+/// POST /battle, POST /battle/roster（ログイン不要）
+pub fn routes(config: &ServerConfig) -> Router {
+    // ログイン不要で 1 回に MAX_BATTLE_SIZE 人まで回せるので、gRPC の Battle と同じく IP ごとに制限する
+    let limits = Arc::new(RouteLimits {
+        action: "battle",
+        per_ip: RateLimiter::per_minute(config.instant_rate_per_ip),
+        per_account: None,
+        trust_forwarded_for: config.trust_forwarded_for,
+    });
+    Router::new()
+        .route("/battle", post(battle_handler))
+        .route("/battle/roster", post(roster_battle_handler))
+        .route_layer(middleware::from_fn_with_state(
+            limits,
+            rate_limit::limit_requests,
+        ))
+}
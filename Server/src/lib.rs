@@ -0,0 +1,20 @@
+// battle_server の各バイナリ（main.rs / HelloWorld.rs / simulate.rs）で共有するモジュール
+
+pub mod app;
+pub mod auth;
+pub mod codec;
+pub mod config;
+#[cfg(feature = "mdns")]
+pub mod discovery;
+pub mod engine;
+pub mod error;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod i18n;
+pub mod instant;
+pub mod lobby;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod protocol;
+pub mod rate_limit;
+pub mod roster;
@@ -0,0 +1,1006 @@
+// ===== マッチング API =====
+//
+// POST /join でロビーに入り、締め切り後にまとめてバトルする。
+// ロビーは mode ごとに複数開き、GET /lobbies で一覧を返す。
+// DELETE /tickets/{id} で取り消し（JoinRequest::ticket を指定した参加だけ）、
+// GET /matches で直近の結果を見る。
+// GET /matches/{id}/log でバトルの経過（クライアントのアリーナ表示用）を返す。
+// ロビーの本体は LobbyManager で、gRPC（grpc.rs）からも同じものを使う。
+// ロビーはプロセスのメモリにしかないので、取り消しやログの取得は参加したのと同じプロセスに
+// 届く必要がある（deployment.yml はレプリカ 1 つ、service.yml は sessionAffinity: ClientIP）。
+
+use crate::auth::{self, AuthUser, SharedAuth};
+use crate::codec::{Negotiated, WireFormat};
+use crate::config::{AbandonPolicy, ModeRules, ServerConfig};
+use crate::engine::{self, Fighter, Targeting};
+use crate::error::{AppError, AppJson};
+use crate::rate_limit::{self, RateLimiter, RouteLimits};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    routing::{delete, get, post},
+    Extension, Router,
+};
+use battle_api::{
+    t, AbandonedPlayer, BattleLog, CharacterClass, ClientCharacterResult, GameMode, JoinRequest,
+    JoinResponse, LobbyInfo, LogFighter, MatchRecord, StatBudget,
+};
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio::time::{sleep_until, Instant};
+
+// ===== マッチング用の構造体 =====
+
+type ResultSender = oneshot::Sender<Result<JoinResponse, AppError>>;
+
+struct PlayerEntry {
+    ticket: String,
+    character: Fighter,  // 本人（アカウント名）
+    squad: Vec<Fighter>, // 一緒に参加したキャラクター（JoinRequest::squad の順）
+    tx: ResultSender,    // このプレイヤーへの結果送信口
+}
+
+impl PlayerEntry {
+    /// 本人、squad の順
+    fn fighters(&self) -> impl Iterator<Item = &Fighter> {
+        std::iter::once(&self.character).chain(&self.squad)
+    }
+
+    fn size(&self) -> usize {
+        1 + self.squad.len()
+    }
+}
+
+struct Lobby {
+    id: u64,
+    mode: GameMode,
+    rules: ModeRules,
+    players: Vec<PlayerEntry>,
+    cancelled: Vec<String>, // DELETE /tickets で抜けたキャラクター名
+    deadline: Instant,      // マッチ確定時刻
+}
+
+struct LobbyState {
+    lobbies: Vec<Lobby>, // 締め切り前のロビー（古い順）
+    next_lobby_id: u64,
+    history: MatchHistory,
+    leaderboard: HashMap<String, PlayerStats>,
+}
+
+/// 観戦用に流すロビーの変化（WatchMatch など）
+#[derive(Clone, Debug)]
+pub enum LobbyEvent {
+    /// ロビーの待機人数が変わった
+    Waiting {
+        lobby: u64,
+        players: usize,
+        deadline: Instant,
+    },
+    /// 締め切ってバトルを始めた（参加しているチケット）
+    Started { tickets: Vec<String> },
+    /// バトルが終わった（チケットごとの結果）
+    Finished {
+        results: Vec<(String, JoinResponse)>,
+    },
+    /// 取り消し・切断でロビーから外れた
+    Cancelled { ticket: String },
+}
+
+impl LobbyEvent {
+    /// Waiting のロビー id
+    pub fn lobby(&self) -> Option<u64> {
+        match self {
+            LobbyEvent::Waiting { lobby, .. } => Some(*lobby),
+            _ => None,
+        }
+    }
+}
+
+impl Lobby {
+    /// squad も1人ずつ数える
+    fn size(&self) -> usize {
+        self.players.iter().map(PlayerEntry::size).sum()
+    }
+
+    fn waiting(&self) -> LobbyEvent {
+        LobbyEvent::Waiting {
+            lobby: self.id,
+            players: self.size(),
+            deadline: self.deadline,
+        }
+    }
+}
+
+// 観戦者が追いつけないときに捨てるまでの件数
+const EVENT_BUFFER: usize = 256;
+
+// ===== マッチ履歴 =====
+
+struct MatchHistory {
+    next_id: u64,
+    records: VecDeque<MatchRecord>, // 古い順
+    logs: VecDeque<Arc<BattleLog>>, // 古い順（バトルしたマッチだけ）
+}
+
+impl MatchHistory {
+    fn new() -> Self {
+        Self {
+            next_id: 1,
+            records: VecDeque::new(),
+            logs: VecDeque::new(),
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn push(&mut self, record: MatchRecord, log: Option<BattleLog>, limit: usize) {
+        self.records.push_back(record);
+        while self.records.len() > limit {
+            self.records.pop_front();
+        }
+        self.logs.extend(log.map(Arc::new));
+        while self.logs.len() > limit {
+            self.logs.pop_front();
+        }
+    }
+
+    fn log(&self, id: u64) -> Option<Arc<BattleLog>> {
+        self.logs.iter().find(|log| log.match_id == id).cloned()
+    }
+}
+
+// ===== ランキング =====
+
+#[derive(Clone, Debug, Default)]
+struct PlayerStats {
+    matches: u32,
+    wins: u32,
+    best_rank: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub matches: u32,
+    pub wins: u32,
+    pub best_rank: usize,
+}
+
+// ===== ロビー本体 =====
+
+pub struct LobbyManager {
+    config: ServerConfig,
+    state: Mutex<LobbyState>,
+    events: broadcast::Sender<LobbyEvent>,
+}
+
+pub type SharedLobby = Arc<LobbyManager>;
+
+impl LobbyManager {
+    pub fn new(config: ServerConfig) -> SharedLobby {
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        Arc::new(Self {
+            config,
+            state: Mutex::new(LobbyState {
+                lobbies: Vec::new(),
+                next_lobby_id: 1,
+                history: MatchHistory::new(),
+                leaderboard: HashMap::new(),
+            }),
+            events,
+        })
+    }
+
+    fn notify(&self, event: LobbyEvent) {
+        // 観戦者がいなければ捨てる
+        let _ = self.events.send(event);
+    }
+
+    /// ロビーに入り、マッチが終わるまで待って結果を返す
+    pub async fn join(
+        self: &Arc<Self>,
+        user: &AuthUser,
+        req: JoinRequest,
+    ) -> Result<JoinResponse, AppError> {
+        validate_join(&req, &self.config.stat_budget)?;
+
+        // キャラクター名はアカウント名に固定する
+        if !req.name.is_empty() && req.name != user.name {
+            return Err(AppError::Forbidden(format!(
+                "cannot join as '{}' while logged in as '{}'",
+                req.name, user.name
+            )));
+        }
+
+        let ticket = req.ticket.clone().unwrap_or_else(new_ticket);
+        let (tx, rx) = oneshot::channel::<Result<JoinResponse, AppError>>();
+
+        {
+            let mut state = self.state.lock().await;
+
+            let players = || state.lobbies.iter().flat_map(|l| &l.players);
+            if players().any(|p| p.ticket == ticket) {
+                return Err(AppError::Conflict(format!(
+                    "ticket '{}' is already waiting in a lobby",
+                    ticket
+                )));
+            }
+            if players().any(|p| p.character.name == user.name) {
+                return Err(AppError::Conflict(format!(
+                    "'{}' is already waiting in a lobby",
+                    user.name
+                )));
+            }
+            // 全ロビーの待機人数の上限。一番早い締め切り（= 空きが出る時刻）まで待ってもらう
+            let size = 1 + req.squad.len();
+            if players().map(PlayerEntry::size).sum::<usize>() + size
+                > self.config.max_pending_joins
+            {
+                let now = Instant::now();
+                let retry_after = state
+                    .lobbies
+                    .iter()
+                    .map(|l| l.deadline.saturating_duration_since(now))
+                    .min()
+                    .unwrap_or_default();
+                return Err(AppError::TooManyRequests {
+                    message: "lobbies are full, try again after the current match".to_string(),
+                    retry_after,
+                });
+            }
+
+            let mode = match req.lobby {
+                Some(id) => state
+                    .lobbies
+                    .iter()
+                    .find(|l| l.id == id)
+                    .map(|l| l.mode)
+                    .ok_or_else(|| AppError::NotFound(format!("lobby {} is not open", id)))?,
+                None => req.mode,
+            };
+            let max_squad = self.config.mode_rules(mode).max_squad;
+            if size > max_squad {
+                return Err(AppError::invalid_field(
+                    "squad",
+                    format!(
+                        "{} lobbies take at most {} characters per join",
+                        mode.as_str(),
+                        max_squad
+                    ),
+                ));
+            }
+
+            let has_room = |l: &Lobby| l.size() + size <= l.rules.capacity;
+            let index = match req.lobby {
+                Some(id) => {
+                    let index = state.lobbies.iter().position(|l| l.id == id);
+                    match index {
+                        Some(index) if has_room(&state.lobbies[index]) => index,
+                        _ => {
+                            return Err(AppError::Conflict(format!(
+                                "lobby {} has no room for {} characters",
+                                id, size
+                            )))
+                        }
+                    }
+                }
+                // squad が入りきらなければ同じ mode でも別のロビーを開く
+                None => match state
+                    .lobbies
+                    .iter()
+                    .position(|l| l.mode == mode && has_room(l))
+                {
+                    Some(index) => index,
+                    None => {
+                        // 入れるロビーが無い -> 1人目の参加者
+                        let id = state.next_lobby_id;
+                        state.next_lobby_id += 1;
+                        let rules = self.config.mode_rules(mode);
+                        let deadline = Instant::now() + rules.wait;
+
+                        let manager = self.clone();
+                        tokio::spawn(async move {
+                            sleep_until(deadline).await;
+                            manager.finalize_match(id).await;
+                        });
+
+                        state.lobbies.push(Lobby {
+                            id,
+                            mode,
+                            rules,
+                            players: Vec::new(),
+                            cancelled: Vec::new(),
+                            deadline,
+                        });
+                        state.lobbies.len() - 1
+                    }
+                },
+            };
+            let lobby = &mut state.lobbies[index];
+
+            let (hp, atk) = req.class.derive_stats(req.hp, req.atk);
+            let character = Fighter {
+                name: user.name.clone(),
+                hp,
+                atk,
+                is_client: true,
+                targeting: req.targeting,
+            };
+            let squad: Vec<Fighter> = req
+                .squad
+                .iter()
+                .map(|m| {
+                    let (hp, atk) = m.class.derive_stats(m.hp, m.atk);
+                    Fighter {
+                        name: format!("{}/{}", user.name, m.name),
+                        hp,
+                        atk,
+                        is_client: true,
+                        targeting: m.targeting,
+                    }
+                })
+                .collect();
+
+            println!(
+                "{}",
+                t!(
+                    "lobby-joined",
+                    name = character.name.as_str(),
+                    characters = size,
+                    mode = lobby.mode.as_str(),
+                    lobby = lobby.id
+                )
+            );
+
+            lobby.players.push(PlayerEntry {
+                ticket,
+                character,
+                squad,
+                tx,
+            });
+            self.notify(lobby.waiting());
+
+            if lobby.size() >= lobby.rules.capacity {
+                // 定員に達したら締め切りを待たずに始める（締め切りのタスクは何もせず終わる）
+                let manager = self.clone();
+                let id = lobby.id;
+                tokio::spawn(async move {
+                    manager.finalize_match(id).await;
+                });
+            }
+        }
+
+        // 確定タスクが結果を送らずに終わった場合はエラーとして返す
+        // 取り消された場合は Err(JoinCancelled) が届く
+        rx.await.map_err(|_| AppError::MatchAborted)?
+    }
+
+    /// 待機中のプレイヤーをロビーから外す（バトル開始後は取り消せない）
+    ///
+    /// 取り消せるのはクライアントが ticket を指定した参加だけ（new_ticket の ID は受け付けない）
+    pub async fn cancel(&self, user: &AuthUser, ticket: &str) -> Result<(), AppError> {
+        let mut state = self.state.lock().await;
+
+        let not_found =
+            || AppError::NotFound(format!("ticket '{}' is not waiting in any lobby", ticket));
+        if !is_valid_ticket(ticket) {
+            return Err(not_found());
+        }
+
+        let (lobby, pos) = state
+            .lobbies
+            .iter_mut()
+            .find_map(|l| {
+                let pos = l.players.iter().position(|p| p.ticket == ticket)?;
+                Some((l, pos))
+            })
+            .ok_or_else(not_found)?;
+
+        if lobby.players[pos].character.name != user.name {
+            return Err(AppError::Forbidden(
+                "ticket belongs to another account".to_string(),
+            ));
+        }
+
+        let entry = lobby.players.remove(pos);
+        lobby
+            .cancelled
+            .extend(entry.fighters().map(|f| f.name.clone()));
+
+        println!(
+            "{}",
+            t!("lobby-cancelled", name = entry.character.name.as_str())
+        );
+        let _ = entry.tx.send(Err(AppError::JoinCancelled));
+
+        self.notify(LobbyEvent::Cancelled {
+            ticket: entry.ticket,
+        });
+        self.notify(lobby.waiting());
+        Ok(())
+    }
+
+    /// 待機中のチケットの変化を購読する。最初の状態も一緒に返す
+    pub async fn watch(
+        &self,
+        user: &AuthUser,
+        ticket: &str,
+    ) -> Result<(LobbyEvent, broadcast::Receiver<LobbyEvent>), AppError> {
+        // ロックを持ったまま購読して、確認から購読までの間のイベントを落とさない
+        let state = self.state.lock().await;
+
+        let not_found =
+            || AppError::NotFound(format!("ticket '{}' is not waiting in any lobby", ticket));
+        if !is_valid_ticket(ticket) {
+            return Err(not_found());
+        }
+
+        let (lobby, entry) = state
+            .lobbies
+            .iter()
+            .find_map(|l| Some((l, l.players.iter().find(|p| p.ticket == ticket)?)))
+            .ok_or_else(not_found)?;
+
+        if entry.character.name != user.name {
+            return Err(AppError::Forbidden(
+                "ticket belongs to another account".to_string(),
+            ));
+        }
+
+        Ok((lobby.waiting(), self.events.subscribe()))
+    }
+
+    /// 締め切り前のロビー（古い順）
+    pub async fn lobbies(&self) -> Vec<LobbyInfo> {
+        let state = self.state.lock().await;
+        let now = Instant::now();
+        state
+            .lobbies
+            .iter()
+            .map(|l| LobbyInfo {
+                id: l.id,
+                mode: l.mode,
+                players: l.size(),
+                capacity: l.rules.capacity,
+                max_squad: l.rules.max_squad,
+                battle_size: l.rules.battle_size,
+                seconds_left: l
+                    .deadline
+                    .saturating_duration_since(now)
+                    .as_secs_f64()
+                    .ceil() as u64,
+            })
+            .collect()
+    }
+
+    /// 直近のマッチ履歴（新しい順）
+    pub async fn history(&self) -> Vec<MatchRecord> {
+        let state = self.state.lock().await;
+        state.history.records.iter().rev().cloned().collect()
+    }
+
+    /// バトルの経過（履歴から消えたマッチ・全員抜けてバトルしなかったマッチは None）
+    pub async fn battle_log(&self, id: u64) -> Option<Arc<BattleLog>> {
+        self.state.lock().await.history.log(id)
+    }
+
+    /// 勝利数の多い順（同数なら最高順位、試合数の順）
+    pub async fn leaderboard(&self, limit: usize) -> Vec<LeaderboardEntry> {
+        let state = self.state.lock().await;
+        let mut entries: Vec<LeaderboardEntry> = state
+            .leaderboard
+            .iter()
+            .map(|(name, stats)| LeaderboardEntry {
+                name: name.clone(),
+                matches: stats.matches,
+                wins: stats.wins,
+                best_rank: stats.best_rank.unwrap_or_default(),
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(a.best_rank.cmp(&b.best_rank))
+                .then(b.matches.cmp(&a.matches))
+                .then(a.name.cmp(&b.name))
+        });
+        entries.truncate(limit);
+        entries
+    }
+
+    // ===== マッチ確定処理 =====
+
+    async fn finalize_match(self: Arc<Self>, lobby_id: u64) {
+        let policy = self.config.abandon_policy;
+        let lobby = {
+            let mut state = self.state.lock().await;
+            // 定員で先に始めていれば、締め切りのタスクからは見つからない
+            let Some(pos) = state.lobbies.iter().position(|l| l.id == lobby_id) else {
+                return;
+            };
+            state.lobbies.remove(pos)
+        };
+
+        // /join のリクエストが切断されると受信側が drop されるので、
+        // バトル前に送信口が閉じているプレイヤーを抜けたものとして扱う
+        let (players, left): (Vec<PlayerEntry>, Vec<PlayerEntry>) =
+            lobby.players.into_iter().partition(|p| !p.tx.is_closed());
+
+        let mut abandoned: Vec<AbandonedPlayer> = lobby
+            .cancelled
+            .into_iter()
+            .map(|name| AbandonedPlayer {
+                name,
+                stage: "cancelled".to_string(),
+                handled_as: Some(AbandonPolicy::Remove.as_str().to_string()),
+            })
+            .collect();
+
+        for p in &left {
+            println!(
+                "{}",
+                t!("lobby-disconnected", name = p.character.name.as_str())
+            );
+            self.notify(LobbyEvent::Cancelled {
+                ticket: p.ticket.clone(),
+            });
+            abandoned.extend(p.fighters().map(|f| AbandonedPlayer {
+                name: f.name.clone(),
+                stage: "lobby".to_string(),
+                handled_as: Some(policy.as_str().to_string()),
+            }));
+        }
+
+        // 全員抜けたロビーは履歴に残さない
+        if players.is_empty() && abandoned.is_empty() {
+            return;
+        }
+
+        // 結果にマッチ ID を入れるので先に採番する
+        let id = self.state.lock().await.history.next_id();
+
+        // プレイヤーのキャラクターを参加順（本人、squad の順）で先頭に並べる
+        let mut all_chars: Vec<Fighter> = players
+            .iter()
+            .flat_map(PlayerEntry::fighters)
+            .cloned()
+            .collect();
+
+        if policy == AbandonPolicy::ConvertToNpc {
+            all_chars.extend(
+                left.iter()
+                    .flat_map(PlayerEntry::fighters)
+                    .map(|f| Fighter {
+                        is_client: false,
+                        ..f.clone()
+                    }),
+            );
+        }
+
+        let outcome = if players.is_empty() {
+            // 誰も残っていなければバトルしない
+            None
+        } else {
+            self.notify(LobbyEvent::Started {
+                tickets: players.iter().map(|p| p.ticket.clone()).collect(),
+            });
+
+            // NPC はその場で作り、バトルだけ seed から回す（ログの seed で再計算できる）
+            let mut rng = rand::thread_rng();
+            let seed = rng.gen();
+            while all_chars.len() < lobby.rules.battle_size {
+                let id = all_chars.len();
+                all_chars.push(Fighter {
+                    name: format!("NPC_{}", id),
+                    hp: engine::NPC_HP.sample(&mut rng),
+                    atk: engine::NPC_ATK.sample(&mut rng),
+                    is_client: false,
+                    targeting: Targeting::Random,
+                });
+            }
+            let fighters: Vec<LogFighter> = all_chars.iter().map(LogFighter::from).collect();
+            let mut events = Vec::new();
+            let outcome = engine::run_battle_with(
+                std::mem::take(&mut all_chars),
+                &mut engine::battle_rng(seed),
+                |event| events.push((*event).into()),
+            );
+            let log = BattleLog {
+                match_id: id,
+                seed: Some(seed),
+                fighters,
+                events,
+            };
+            Some((outcome, log))
+        };
+        let (outcome, log) = outcome.unzip();
+
+        let winner = outcome
+            .as_ref()
+            .and_then(|o| o.winner())
+            .map(|f| f.name.clone());
+        let npc_count = outcome
+            .as_ref()
+            .map(|o| o.fighters.iter().filter(|f| !f.is_client).count())
+            .unwrap_or_default();
+        let ranks = outcome.as_ref().map(|o| o.ranks()).unwrap_or_default();
+        let player_names: Vec<String> = players
+            .iter()
+            .flat_map(PlayerEntry::fighters)
+            .map(|f| f.name.clone())
+            .collect();
+
+        // all_chars の添字 i のキャラクターの結果
+        let character_result = |i: usize, name: &str| {
+            let rank = ranks[i];
+            ClientCharacterResult {
+                name: name.to_string(),
+                rank,
+                final_hp: outcome.as_ref().map(|o| o.fighters[i].hp).unwrap_or(-1),
+                is_winner: rank == 1,
+            }
+        };
+
+        let mut results = Vec::with_capacity(players.len());
+        let mut index = 0;
+        for player in players {
+            let own = character_result(index, &player.character.name);
+            let squad = player
+                .squad
+                .iter()
+                .enumerate()
+                .map(|(k, f)| character_result(index + 1 + k, &f.name))
+                .collect();
+            index += player.size();
+
+            let result = JoinResponse {
+                name: own.name,
+                rank: own.rank,
+                final_hp: own.final_hp,
+                is_winner: own.is_winner,
+                match_id: Some(id),
+                squad,
+            };
+            if player.tx.send(Ok(result.clone())).is_err() {
+                // バトル中に切断された
+                println!(
+                    "{}",
+                    t!(
+                        "lobby-result-undeliverable",
+                        name = player.character.name.as_str()
+                    )
+                );
+                let names =
+                    std::iter::once(&result.name).chain(result.squad.iter().map(|r| &r.name));
+                abandoned.extend(names.map(|name| AbandonedPlayer {
+                    name: name.clone(),
+                    stage: "result".to_string(),
+                    handled_as: None,
+                }));
+            }
+            results.push((player.ticket, result));
+        }
+
+        let finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut state = self.state.lock().await;
+
+        // squad のキャラクターも1人ずつ集計する
+        let characters = results.iter().flat_map(|(_, result)| {
+            std::iter::once((&result.name, result.rank, result.is_winner))
+                .chain(result.squad.iter().map(|r| (&r.name, r.rank, r.is_winner)))
+        });
+        for (name, rank, is_winner) in characters {
+            let stats = state.leaderboard.entry(name.clone()).or_default();
+            stats.matches += 1;
+            stats.wins += u32::from(is_winner);
+            stats.best_rank = Some(stats.best_rank.map_or(rank, |r| r.min(rank)));
+        }
+
+        state.history.push(
+            MatchRecord {
+                id,
+                finished_at,
+                players: player_names,
+                npc_count,
+                winner,
+                abandoned,
+                mode: lobby.mode,
+            },
+            log,
+            self.config.history_limit,
+        );
+
+        if !results.is_empty() {
+            self.notify(LobbyEvent::Finished { results });
+        }
+    }
+}
+
+fn validate_join(req: &JoinRequest, budget: &StatBudget) -> Result<(), AppError> {
+    if req.mode == GameMode::Unknown {
+        return Err(AppError::invalid_field(
+            "mode",
+            format!(
+                "mode must be one of {}",
+                GameMode::ALL.map(GameMode::as_str).join(", ")
+            ),
+        ));
+    }
+    validate_stats(req.hp, req.atk, budget, "")?;
+    validate_kind(req.class, req.targeting, "")?;
+    for (i, member) in req.squad.iter().enumerate() {
+        let field = format!("squad[{}].", i);
+        if !is_valid_member_name(&member.name) {
+            return Err(AppError::invalid_field(
+                &format!("{}name", field),
+                "squad names must be 1-16 letters, digits, '_' or '-'",
+            ));
+        }
+        if req.squad[..i].iter().any(|m| m.name == member.name) {
+            return Err(AppError::invalid_field(
+                &format!("{}name", field),
+                format!("squad name '{}' is used twice", member.name),
+            ));
+        }
+        validate_stats(member.hp, member.atk, budget, &field)?;
+        validate_kind(member.class, member.targeting, &field)?;
+    }
+    if let Some(ticket) = &req.ticket {
+        if !is_valid_ticket(ticket) {
+            return Err(AppError::invalid_field(
+                "ticket",
+                "ticket must be 1-64 characters of [A-Za-z0-9_-]",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Unknown（このビルドが知らない値）は受け付けない。field は validate_stats と同じ接頭辞
+fn validate_kind(class: CharacterClass, targeting: Targeting, field: &str) -> Result<(), AppError> {
+    if class == CharacterClass::Unknown {
+        return Err(AppError::invalid_field(
+            &format!("{}class", field),
+            format!(
+                "class must be one of {}",
+                CharacterClass::ALL.map(CharacterClass::as_str).join(", ")
+            ),
+        ));
+    }
+    if targeting == Targeting::Unknown {
+        return Err(AppError::invalid_field(
+            &format!("{}targeting", field),
+            format!(
+                "targeting must be one of {}",
+                Targeting::ALL.map(Targeting::as_str).join(", ")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// field は squad のときの "squad[0]." などの接頭辞
+fn validate_stats(hp: i32, atk: i32, budget: &StatBudget, field: &str) -> Result<(), AppError> {
+    let hp_field = format!("{}hp", field);
+    if hp <= 0 {
+        return Err(AppError::invalid_field(&hp_field, "hp must be positive"));
+    }
+    if atk < 0 {
+        return Err(AppError::invalid_field(
+            &format!("{}atk", field),
+            "atk must not be negative",
+        ));
+    }
+    // 予算はクラス補正の前の値で確認する
+    match budget.cost(hp, atk) {
+        None => {
+            return Err(AppError::InvalidRequest {
+                message: format!(
+                    "hp and atk must be at least {} and {}",
+                    budget.base_hp, budget.base_atk
+                ),
+                details: Some(serde_json::json!({ "field": hp_field, "stat_budget": budget })),
+            })
+        }
+        Some(cost) if cost > budget.points => {
+            return Err(AppError::InvalidRequest {
+                message: format!(
+                    "stats cost {} points but the budget is {}",
+                    cost, budget.points
+                ),
+                details: Some(serde_json::json!({ "field": hp_field, "stat_budget": budget })),
+            })
+        }
+        Some(_) => {}
+    }
+    Ok(())
+}
+
+fn is_valid_member_name(name: &str) -> bool {
+    (1..=16).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_valid_ticket(ticket: &str) -> bool {
+    (1..=64).contains(&ticket.len())
+        && ticket
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// ticket を省略した参加の内部用 ID。
+/// クライアントには返せないので、':' を入れて DELETE /tickets や watch で指定できない形にする
+fn new_ticket() -> String {
+    format!("auto:{:032x}", rand::thread_rng().gen::<u128>())
+}
+
+// ===== /join ハンドラ =====
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/v1/join",
+    tag = "matchmaking",
+    request_body = JoinRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "match finished", content(
+            ("application/json" = JoinResponse),
+            ("application/msgpack" = JoinResponse),
+            ("application/cbor" = JoinResponse),
+        )),
+        (status = 401, description = "not logged in", body = ErrorBody),
+        (status = 404, description = "lobby is not open", body = ErrorBody),
+        (status = 409, description = "already waiting or cancelled", body = ErrorBody),
+        (status = 429, description = "rate limited or lobbies full", body = ErrorBody),
+        (status = 503, description = "match aborted", body = ErrorBody),
+    )
+))]
+async fn join_handler(
+    State(lobby): State<SharedLobby>,
+    Extension(user): Extension<AuthUser>,
+    format: WireFormat,
+    AppJson(req): AppJson<JoinRequest>,
+) -> Result<Negotiated<JoinResponse>, AppError> {
+    let result = lobby.join(&user, req).await?;
+    Ok(Negotiated(format, result))
+}
+
+// ===== DELETE /tickets/{id} ハンドラ =====
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    delete,
+    path = "/v1/tickets/{id}",
+    tag = "matchmaking",
+    params(("id" = String, Path, description = "ticket from the join request")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "cancelled"),
+        (status = 403, description = "ticket belongs to another account", body = ErrorBody),
+        (status = 404, description = "ticket is not waiting", body = ErrorBody),
+    )
+))]
+async fn cancel_ticket_handler(
+    State(lobby): State<SharedLobby>,
+    Extension(user): Extension<AuthUser>,
+    Path(ticket): Path<String>,
+) -> Result<StatusCode, AppError> {
+    lobby.cancel(&user, &ticket).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ===== /lobbies ハンドラ =====
+
+/// 締め切り前のロビー（古い順）
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/v1/lobbies",
+    tag = "matchmaking",
+    responses((status = 200, description = "open lobbies", content(
+        ("application/json" = Vec<LobbyInfo>),
+        ("application/msgpack" = Vec<LobbyInfo>),
+        ("application/cbor" = Vec<LobbyInfo>),
+    )))
+))]
+async fn lobbies_handler(
+    State(lobby): State<SharedLobby>,
+    format: WireFormat,
+) -> Negotiated<Vec<LobbyInfo>> {
+    Negotiated(format, lobby.lobbies().await)
+}
+
+// ===== /matches ハンドラ =====
+
+/// 直近のマッチ履歴（新しい順）
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/v1/matches",
+    tag = "matchmaking",
+    responses((status = 200, description = "recent matches", content(
+        ("application/json" = Vec<MatchRecord>),
+        ("application/msgpack" = Vec<MatchRecord>),
+        ("application/cbor" = Vec<MatchRecord>),
+    )))
+))]
+async fn matches_handler(
+    State(lobby): State<SharedLobby>,
+    format: WireFormat,
+) -> Negotiated<Vec<MatchRecord>> {
+    Negotiated(format, lobby.history().await)
+}
+
+// ===== /matches/{id}/log ハンドラ =====
+
+/// バトルの経過。イベントが数千件になるので MessagePack / CBOR 推奨
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/v1/matches/{id}/log",
+    tag = "matchmaking",
+    params(("id" = u64, Path, description = "JoinResponse.match_id or MatchRecord.id")),
+    responses(
+        (status = 200, description = "battle log", content(
+            ("application/json" = BattleLog),
+            ("application/msgpack" = BattleLog),
+            ("application/cbor" = BattleLog),
+        )),
+        (status = 404, description = "match is no longer kept or had no battle", body = ErrorBody),
+    )
+))]
+async fn match_log_handler(
+    State(lobby): State<SharedLobby>,
+    Path(id): Path<u64>,
+    format: WireFormat,
+) -> Result<Negotiated<BattleLog>, AppError> {
+    let log = lobby
+        .battle_log(id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("no battle log for match {}", id)))?;
+    Ok(Negotiated(format, BattleLog::clone(&log)))
+}
+
+// ===== ルーター =====
+
+/// POST /join, DELETE /tickets/{id}, GET /lobbies, GET /matches, GET /matches/{id}/log
+pub fn routes(lobby: SharedLobby, auth: SharedAuth) -> Router {
+    let config = &lobby.config;
+    let join_limits = Arc::new(RouteLimits {
+        action: "join",
+        per_ip: RateLimiter::per_minute(config.join_rate_per_ip),
+        per_account: RateLimiter::per_minute(config.join_rate_per_account),
+        trust_forwarded_for: config.trust_forwarded_for,
+    });
+
+    // /join は回数制限付き
+    let join = Router::new()
+        .route("/join", post(join_handler))
+        .route_layer(middleware::from_fn_with_state(
+            join_limits,
+            rate_limit::limit_requests,
+        ));
+
+    // /join と /tickets はログイン必須
+    let protected = Router::new()
+        .merge(join)
+        .route("/tickets/:id", delete(cancel_ticket_handler))
+        .route_layer(middleware::from_fn_with_state(auth, auth::require_auth));
+
+    Router::new()
+        .merge(protected)
+        .route("/lobbies", get(lobbies_handler))
+        .route("/matches", get(matches_handler))
+        .route("/matches/:id/log", get(match_log_handler))
+        .with_state(lobby)
+}
@@ -0,0 +1,101 @@
+use crate::config::ServerConfig;
+use crate::{auth, instant, lobby, protocol};
+use axum::{routing::get, Json, Router};
+use battle_api::*;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+// ===== OpenAPI ドキュメント =====
+//
+// 各ハンドラの #[utoipa::path] と battle_api の型から生成する。
+// 無効化した API（BATTLE_ENABLE_*）のパスは tag で見分けて外す（型の定義は残る）。
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "battle_server"),
+    paths(
+        protocol::version_handler,
+        auth::register_handler,
+        auth::login_handler,
+        lobby::join_handler,
+        lobby::cancel_ticket_handler,
+        lobby::lobbies_handler,
+        lobby::matches_handler,
+        lobby::match_log_handler,
+        instant::battle_handler,
+        instant::roster_battle_handler,
+    ),
+    components(schemas(
+        ServerInfo,
+        ErrorBody,
+        StatBudget,
+        CharacterClass,
+        Targeting,
+        CredentialsRequest,
+        TokenResponse,
+        GameMode,
+        LobbyInfo,
+        SquadMember,
+        JoinRequest,
+        JoinResponse,
+        AbandonedPlayer,
+        MatchRecord,
+        BattleLog,
+        LogFighter,
+        LogEvent,
+        StatRange,
+        ClientCharacterInput,
+        BattleRequest,
+        ClientCharacterResult,
+        Standing,
+        BattleResult,
+    )),
+    modifiers(&BearerAuth),
+)]
+struct ApiDoc;
+
+/// /v1/join などで使う `Authorization: Bearer <token>`
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// 公開していない API の tag（app::router が組まないルート）
+fn disabled_tags(config: &ServerConfig) -> Vec<&'static str> {
+    let mut tags = Vec::new();
+    if !config.enable_matchmaking {
+        // /register・/login はマッチングと一緒にしか公開しない
+        tags.extend(["matchmaking", "auth"]);
+    }
+    if !config.enable_instant {
+        tags.push("instant");
+    }
+    tags
+}
+
+/// 有効な API のパスだけを載せたドキュメント
+pub fn document(config: &ServerConfig) -> utoipa::openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    let disabled = disabled_tags(config);
+    doc.paths.paths.retain(|_, item| {
+        !item
+            .operations
+            .values()
+            .flat_map(|op| op.tags.iter().flatten())
+            .any(|tag| disabled.contains(&tag.as_str()))
+    });
+    doc
+}
+
+/// GET /openapi.json
+pub fn routes(config: &ServerConfig) -> Router {
+    let doc = document(config);
+    Router::new().route("/openapi.json", get(move || async move { Json(doc) }))
+}
@@ -0,0 +1,117 @@
+use crate::config::ServerConfig;
+use crate::error::{AppError, AppJson};
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{any, get},
+    Router,
+};
+use battle_api::{capability, ServerInfo, PROTOCOL_HEADER, PROTOCOL_VERSION};
+
+// ===== プロトコルのバージョン確認 =====
+//
+// クライアントは X-Battle-Protocol に自分のバージョンを入れて送る。
+// ヘッダが無いリクエスト（curl など）は通す。
+
+/// これより古いクライアントは 426 で断る。互換性を切るときに上げる
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+fn client_protocol(req: &Request) -> Result<Option<u32>, AppError> {
+    let Some(value) = req.headers().get(PROTOCOL_HEADER) else {
+        return Ok(None);
+    };
+    let version: u32 = value
+        .to_str()
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or_else(|| {
+            AppError::invalid_field(PROTOCOL_HEADER, "protocol version must be an integer")
+        })?;
+    if version < MIN_PROTOCOL_VERSION {
+        return Err(AppError::ClientTooOld {
+            client: version,
+            min: MIN_PROTOCOL_VERSION,
+        });
+    }
+    // 新しいクライアントはそのまま受け付ける（知らないフィールドは無視される）
+    Ok(Some(version))
+}
+
+/// 古いクライアントを断り、レスポンスにサーバのバージョンを付ける
+pub async fn check_protocol(req: Request, next: Next) -> Response {
+    let mut response = match client_protocol(&req) {
+        Ok(_) => next.run(req).await,
+        Err(err) => err.into_response(),
+    };
+    response
+        .headers_mut()
+        .insert(PROTOCOL_HEADER, HeaderValue::from(PROTOCOL_VERSION));
+    response
+}
+
+// ===== GET /v1/version =====
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/v1/version",
+    tag = "meta",
+    responses((status = 200, description = "server and protocol version", body = ServerInfo))
+))]
+async fn version_handler(info: ServerInfo) -> AppJson<ServerInfo> {
+    AppJson(info)
+}
+
+pub fn server_info(config: &ServerConfig) -> ServerInfo {
+    let mut capabilities = Vec::new();
+    if config.enable_matchmaking {
+        capabilities.push(capability::MATCHMAKING.to_string());
+        capabilities.push(capability::BATTLE_LOG.to_string());
+        capabilities.push(capability::LOBBIES.to_string());
+        capabilities.push(capability::SQUAD.to_string());
+        capabilities.push(capability::VERIFY.to_string());
+    }
+    if config.enable_instant {
+        capabilities.push(capability::INSTANT.to_string());
+        capabilities.push(capability::ROSTER.to_string());
+    }
+    capabilities.push(capability::MSGPACK.to_string());
+    capabilities.push(capability::CBOR.to_string());
+    if cfg!(feature = "openapi") {
+        capabilities.push(capability::OPENAPI.to_string());
+    }
+
+    ServerInfo {
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        protocol_version: PROTOCOL_VERSION,
+        min_protocol_version: MIN_PROTOCOL_VERSION,
+        capabilities,
+        stat_budget: config.enable_matchmaking.then_some(config.stat_budget),
+    }
+}
+
+/// GET /version（プロトコルの確認より前に呼べるように check_protocol の外に置く）
+pub fn routes(config: &ServerConfig) -> Router {
+    let info = server_info(config);
+    Router::new().route("/version", get(move || version_handler(info.clone())))
+}
+
+// ===== バージョン導入前のパス =====
+
+/// /v1 が付く前のクライアントには 404 ではなく「更新が必要」と返す
+pub fn legacy_routes() -> Router {
+    let too_old = || async {
+        AppError::ClientTooOld {
+            client: 0,
+            min: MIN_PROTOCOL_VERSION,
+        }
+    };
+    Router::new()
+        .route("/join", any(too_old))
+        .route("/tickets/:id", any(too_old))
+        .route("/matches", any(too_old))
+        .route("/register", any(too_old))
+        .route("/login", any(too_old))
+        .route("/battle", any(too_old))
+}
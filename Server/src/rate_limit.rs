@@ -0,0 +1,190 @@
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+    Extension,
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// ===== トークンバケット =====
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Default)]
+struct Buckets {
+    map: HashMap<String, Bucket>,
+    /// 前回の掃除から増えたキーの数
+    inserted: usize,
+}
+
+/// キー（IP / アカウント名）ごとのトークンバケット
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<Buckets>,
+}
+
+// 前回の掃除から (今の件数, これ) の大きい方だけキーが増えたら、満タンに戻ったバケットを掃除する。
+// 掃除は全件を見るが、その間に同じくらい挿入があるので1回あたりは償却 O(1)
+const PRUNE_MIN_INSERTS: usize = 1024;
+
+impl RateLimiter {
+    /// 1分あたり `per_minute` 回（同じ回数までのバースト可）。0 なら制限しない
+    pub fn per_minute(per_minute: u32) -> Option<Self> {
+        if per_minute == 0 {
+            return None;
+        }
+        Some(Self {
+            capacity: per_minute as f64,
+            refill_per_sec: per_minute as f64 / 60.0,
+            buckets: Mutex::new(Buckets::default()),
+        })
+    }
+
+    /// 1回分消費する。足りなければ次に使えるまでの時間を返す
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let Buckets { map, inserted } = &mut *buckets;
+
+        if !map.contains_key(key) {
+            *inserted += 1;
+            if *inserted >= map.len().max(PRUNE_MIN_INSERTS) {
+                let full_after = self.capacity / self.refill_per_sec;
+                map.retain(|_, b| now.duration_since(b.updated).as_secs_f64() < full_after);
+                *inserted = 0;
+            }
+        }
+
+        let bucket = map.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / self.refill_per_sec;
+            Err(Duration::from_secs_f64(wait))
+        }
+    }
+}
+
+// ===== ルートごとのミドルウェア =====
+
+/// /join・/register・/login・/battle に付ける制限
+pub struct RouteLimits {
+    /// エラーメッセージに出す操作名（"join" など）
+    pub action: &'static str,
+    pub per_ip: Option<RateLimiter>,
+    /// ログイン後のルートだけ（require_auth の内側に置いたとき）
+    pub per_account: Option<RateLimiter>,
+    /// Ingress / LB 越しのときは X-Forwarded-For の末尾（プロキシが付け足した値）をクライアント IP とみなす
+    pub trust_forwarded_for: bool,
+}
+
+pub type SharedRouteLimits = Arc<RouteLimits>;
+
+/// 回数制限のキーにする接続元 IP（gRPC のメタデータからも同じように決める）
+///
+/// X-Forwarded-For の先頭はクライアントが好きに書けるので、信頼するプロキシが最後に付け足した
+/// 末尾の値を使う（先頭を使うと値を変えるだけで制限をすり抜けられる）
+pub fn client_ip(
+    forwarded_for: Option<&str>,
+    peer: Option<SocketAddr>,
+    trust_forwarded_for: bool,
+) -> String {
+    if trust_forwarded_for {
+        let forwarded = forwarded_for
+            .and_then(|v| v.rsplit(',').next())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        if let Some(ip) = forwarded {
+            return ip;
+        }
+    }
+    peer.map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn too_many(action: &str, scope: &str, retry_after: Duration) -> AppError {
+    AppError::TooManyRequests {
+        message: format!("too many {} requests from this {}", action, scope),
+        retry_after,
+    }
+}
+
+/// IP ごと・アカウントごとに回数を制限する（アカウントごとの制限は require_auth の内側でだけ効く）
+pub async fn limit_requests(
+    State(limits): State<SharedRouteLimits>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    user: Option<Extension<AuthUser>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(limiter) = &limits.per_ip {
+        let forwarded_for = req
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok());
+        let ip = client_ip(
+            forwarded_for,
+            connect_info.map(|ConnectInfo(addr)| addr),
+            limits.trust_forwarded_for,
+        );
+        limiter
+            .check(&ip)
+            .map_err(|wait| too_many(limits.action, "address", wait))?;
+    }
+
+    if let (Some(limiter), Some(Extension(user))) = (&limits.per_account, user) {
+        limiter
+            .check(&user.name)
+            .map_err(|wait| too_many(limits.action, "account", wait))?;
+    }
+
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_ip_uses_the_hop_added_by_the_proxy() {
+        let peer = Some(SocketAddr::from(([10, 0, 0, 1], 4000)));
+        let forwarded = Some("203.0.113.9, 198.51.100.7");
+        assert_eq!(client_ip(forwarded, peer, true), "198.51.100.7");
+        assert_eq!(
+            client_ip(Some(" 198.51.100.7 "), peer, true),
+            "198.51.100.7"
+        );
+        // 信頼しない設定なら見ない
+        assert_eq!(client_ip(forwarded, peer, false), "10.0.0.1");
+        assert_eq!(client_ip(Some(""), peer, true), "10.0.0.1");
+        assert_eq!(client_ip(None, None, true), "unknown");
+    }
+
+    #[test]
+    fn rotating_the_leftmost_entry_does_not_reset_the_limit() {
+        let limiter = RateLimiter::per_minute(2).unwrap();
+        for spoofed in ["1.1.1.1", "2.2.2.2", "3.3.3.3"] {
+            let header = format!("{}, 198.51.100.7", spoofed);
+            let result = limiter.check(&client_ip(Some(&header), None, true));
+            assert_eq!(result.is_ok(), spoofed != "3.3.3.3", "{}", spoofed);
+        }
+    }
+}
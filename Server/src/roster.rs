@@ -0,0 +1,17 @@
+// ===== ロスターファイル =====
+//
+// 形式の定義と検証はクライアントの書き出しと共有するので battle_api::roster にある。
+
+use crate::error::AppError;
+
+pub use battle_api::roster::*;
+pub use battle_api::CharacterClass;
+
+impl From<RosterError> for AppError {
+    fn from(err: RosterError) -> Self {
+        match err.field {
+            Some(field) => AppError::invalid_field(&field, err.message),
+            None => AppError::invalid(err.message),
+        }
+    }
+}
@@ -0,0 +1,454 @@
+// モンテカルロでバランスを確認するためのバッチシミュレータ
+//
+//   cargo run --release --bin simulate -- --config dist.json --battles 10000 --format csv
+//
+// 設定ファイル（JSON、全項目省略可）:
+//   {
+//     "battle_size": 100,
+//     "players": { "count": 1, "hp": { "min": 80, "max": 119 }, "atk": { "min": 5, "max": 19 } },
+//     "npcs":    { "hp": { "min": 80, "max": 119 }, "atk": { "min": 5, "max": 19 } },
+//     "bucket":  { "hp": 10, "atk": 5 }
+//   }
+
+use battle_server::engine::{self, Fighter, StatRange, Targeting, NPC_ATK, NPC_HP};
+use clap::{Parser, ValueEnum};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+// ===== コマンドライン =====
+
+#[derive(Parser)]
+#[command(about = "Run many seeded battles in parallel and report win rate per stat bucket")]
+struct Args {
+    /// ステータス分布の設定ファイル（JSON）。省略時はロビーと同じ NPC 範囲
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// 実行するバトル数
+    #[arg(long, default_value_t = 1000)]
+    battles: u64,
+
+    /// 乱数シード（同じシード・設定なら結果も同じ）
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    #[arg(long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+
+    /// 出力先（省略時は標準出力）
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Csv,
+    Json,
+}
+
+// ===== 設定ファイル =====
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SimConfig {
+    battle_size: usize,
+    players: PlayerDist,
+    npcs: StatDist,
+    bucket: BucketSize,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PlayerDist {
+    count: usize,
+    hp: StatRange,
+    atk: StatRange,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StatDist {
+    hp: StatRange,
+    atk: StatRange,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BucketSize {
+    hp: i32,
+    atk: i32,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            battle_size: 100,
+            players: PlayerDist::default(),
+            npcs: StatDist::default(),
+            bucket: BucketSize::default(),
+        }
+    }
+}
+
+impl Default for PlayerDist {
+    fn default() -> Self {
+        // クライアントのランダムキャラと同じ範囲
+        Self {
+            count: 1,
+            hp: NPC_HP,
+            atk: NPC_ATK,
+        }
+    }
+}
+
+impl Default for StatDist {
+    fn default() -> Self {
+        Self {
+            hp: NPC_HP,
+            atk: NPC_ATK,
+        }
+    }
+}
+
+impl Default for BucketSize {
+    fn default() -> Self {
+        Self { hp: 10, atk: 5 }
+    }
+}
+
+impl SimConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.battle_size < 2 {
+            return Err("battle_size must be at least 2".to_string());
+        }
+        if self.players.count > self.battle_size {
+            return Err("players.count must not exceed battle_size".to_string());
+        }
+        let ranges = [
+            ("players.hp", self.players.hp),
+            ("players.atk", self.players.atk),
+            ("npcs.hp", self.npcs.hp),
+            ("npcs.atk", self.npcs.atk),
+        ];
+        for (name, range) in ranges {
+            if !range.is_valid() {
+                return Err(format!("{}: min must not exceed max", name));
+            }
+        }
+        // hello_world と同じく hp は 1 以上、atk は 0 以上
+        for (name, range) in [("players.hp", self.players.hp), ("npcs.hp", self.npcs.hp)] {
+            if range.min <= 0 {
+                return Err(format!("{}: min must be positive", name));
+            }
+        }
+        for (name, range) in [
+            ("players.atk", self.players.atk),
+            ("npcs.atk", self.npcs.atk),
+        ] {
+            if range.min < 0 {
+                return Err(format!("{}: min must not be negative", name));
+            }
+        }
+        if self.bucket.hp <= 0 || self.bucket.atk <= 0 {
+            return Err("bucket sizes must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ===== 集計 =====
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Group {
+    Player,
+    Npc,
+}
+
+/// (グループ, hp バケット下限, atk バケット下限)
+type BucketKey = (Group, i32, i32);
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+struct Tally {
+    samples: u64,
+    wins: u64,
+    rank_sum: u64,
+}
+
+#[derive(Serialize)]
+struct Row {
+    group: Group,
+    hp_min: i32,
+    hp_max: i32,
+    atk_min: i32,
+    atk_max: i32,
+    samples: u64,
+    wins: u64,
+    win_rate: f64,
+    avg_rank: f64,
+}
+
+fn bucket_floor(value: i32, size: i32) -> i32 {
+    value.div_euclid(size) * size
+}
+
+fn simulate_one(config: &SimConfig, seed: u64) -> BTreeMap<BucketKey, Tally> {
+    let mut rng = engine::battle_rng(seed);
+
+    let fighters: Vec<Fighter> = (0..config.battle_size)
+        .map(|i| {
+            let is_client = i < config.players.count;
+            let (hp, atk) = if is_client {
+                (config.players.hp, config.players.atk)
+            } else {
+                (config.npcs.hp, config.npcs.atk)
+            };
+            Fighter {
+                name: format!("C{}", i),
+                hp: hp.sample(&mut rng),
+                atk: atk.sample(&mut rng),
+                is_client,
+                targeting: Targeting::Random,
+            }
+        })
+        .collect();
+
+    // 最終 hp ではなく初期ステータスで分類する
+    let initial: Vec<(i32, i32)> = fighters.iter().map(|f| (f.hp, f.atk)).collect();
+    let outcome = engine::run_battle(fighters, &mut rng);
+
+    let mut tallies = BTreeMap::new();
+    for (i, rank) in outcome.ranks().into_iter().enumerate() {
+        let (hp, atk) = initial[i];
+        let group = if outcome.fighters[i].is_client {
+            Group::Player
+        } else {
+            Group::Npc
+        };
+        let key = (
+            group,
+            bucket_floor(hp, config.bucket.hp),
+            bucket_floor(atk, config.bucket.atk),
+        );
+        let tally: &mut Tally = tallies.entry(key).or_default();
+        tally.samples += 1;
+        tally.wins += u64::from(rank == 1);
+        tally.rank_sum += rank as u64;
+    }
+    tallies
+}
+
+fn merge(
+    mut a: BTreeMap<BucketKey, Tally>,
+    b: BTreeMap<BucketKey, Tally>,
+) -> BTreeMap<BucketKey, Tally> {
+    for (key, t) in b {
+        let tally = a.entry(key).or_default();
+        tally.samples += t.samples;
+        tally.wins += t.wins;
+        tally.rank_sum += t.rank_sum;
+    }
+    a
+}
+
+/// `battles` 戦を並列に回して集計する
+///
+/// バトルごとに seed + i で乱数を作るので、スレッド数に関係なく結果は同じ
+fn simulate(config: &SimConfig, battles: u64, seed: u64) -> BTreeMap<BucketKey, Tally> {
+    (0..battles)
+        .into_par_iter()
+        .map(|i| simulate_one(config, seed.wrapping_add(i)))
+        .reduce(BTreeMap::new, merge)
+}
+
+fn to_rows(config: &SimConfig, tallies: BTreeMap<BucketKey, Tally>) -> Vec<Row> {
+    tallies
+        .into_iter()
+        .map(|((group, hp, atk), t)| Row {
+            group,
+            hp_min: hp,
+            hp_max: hp + config.bucket.hp - 1,
+            atk_min: atk,
+            atk_max: atk + config.bucket.atk - 1,
+            samples: t.samples,
+            wins: t.wins,
+            win_rate: t.wins as f64 / t.samples as f64,
+            avg_rank: t.rank_sum as f64 / t.samples as f64,
+        })
+        .collect()
+}
+
+fn write_rows(out: &mut dyn Write, rows: &[Row], format: Format) -> std::io::Result<()> {
+    match format {
+        Format::Csv => {
+            writeln!(
+                out,
+                "group,hp_min,hp_max,atk_min,atk_max,samples,wins,win_rate,avg_rank"
+            )?;
+            for r in rows {
+                let group = match r.group {
+                    Group::Player => "player",
+                    Group::Npc => "npc",
+                };
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{:.6},{:.3}",
+                    group,
+                    r.hp_min,
+                    r.hp_max,
+                    r.atk_min,
+                    r.atk_max,
+                    r.samples,
+                    r.wins,
+                    r.win_rate,
+                    r.avg_rank
+                )?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, rows)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+fn load_config(path: Option<&PathBuf>) -> Result<SimConfig, String> {
+    let config = match path {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+            serde_json::from_str(&text)
+                .map_err(|e| format!("invalid config {}: {}", path.display(), e))?
+        }
+        None => SimConfig::default(),
+    };
+    config.validate()?;
+    Ok(config)
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let config = load_config(args.config.as_ref())?;
+
+    let rows = to_rows(&config, simulate(&config, args.battles, args.seed));
+
+    let result = match &args.output {
+        Some(path) => {
+            std::fs::File::create(path).and_then(|mut f| write_rows(&mut f, &rows, args.format))
+        }
+        None => write_rows(&mut std::io::stdout().lock(), &rows, args.format),
+    };
+    result.map_err(|e| format!("failed to write output: {}", e))
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("error: {}", msg);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_config() -> SimConfig {
+        SimConfig {
+            battle_size: 10,
+            players: PlayerDist {
+                count: 2,
+                ..PlayerDist::default()
+            },
+            ..SimConfig::default()
+        }
+    }
+
+    type Change = fn(&mut SimConfig);
+
+    #[test]
+    fn stat_ranges_are_checked_like_hello_world() {
+        let cases: [(&str, Change); 4] = [
+            ("players.hp", |c| c.players.hp = StatRange::new(0, 10)),
+            ("npcs.hp", |c| c.npcs.hp = StatRange::new(-5, 10)),
+            ("players.atk", |c| c.players.atk = StatRange::new(-1, 10)),
+            ("npcs.atk", |c| c.npcs.atk = StatRange::new(-3, -1)),
+        ];
+        for (field, change) in cases {
+            let mut config = SimConfig::default();
+            change(&mut config);
+            let err = config.validate().unwrap_err();
+            assert!(err.starts_with(field), "{}: {}", field, err);
+        }
+
+        let mut config = SimConfig::default();
+        config.npcs.atk = StatRange::new(0, 0);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn bucket_floor_rounds_down() {
+        assert_eq!(bucket_floor(80, 10), 80);
+        assert_eq!(bucket_floor(89, 10), 80);
+        assert_eq!(bucket_floor(90, 10), 90);
+        assert_eq!(bucket_floor(-1, 10), -10);
+    }
+
+    #[test]
+    fn one_battle_counts_every_fighter_once() {
+        let config = small_config();
+        let tallies = simulate_one(&config, 7);
+        let total = |f: fn(&Tally) -> u64| tallies.values().map(f).sum::<u64>();
+        assert_eq!(total(|t| t.samples), 10);
+        assert_eq!(total(|t| t.wins), 1);
+        // 順位 1..=10 が 1 回ずつ
+        assert_eq!(total(|t| t.rank_sum), 55);
+        let players: u64 = tallies
+            .iter()
+            .filter(|((group, _, _), _)| *group == Group::Player)
+            .map(|(_, t)| t.samples)
+            .sum();
+        assert_eq!(players, 2);
+        for (_, hp, atk) in tallies.keys() {
+            assert_eq!(hp % config.bucket.hp, 0);
+            assert_eq!(atk % config.bucket.atk, 0);
+        }
+    }
+
+    #[test]
+    fn merge_adds_up_tallies() {
+        let key = (Group::Npc, 80, 5);
+        let other = (Group::Player, 90, 10);
+        let tally = |samples, wins, rank_sum| Tally {
+            samples,
+            wins,
+            rank_sum,
+        };
+        let a = BTreeMap::from([(key, tally(3, 1, 6))]);
+        let b = BTreeMap::from([(key, tally(2, 0, 9)), (other, tally(1, 1, 1))]);
+        let merged = merge(a, b);
+        assert_eq!(merged[&key], tally(5, 1, 15));
+        assert_eq!(merged[&other], tally(1, 1, 1));
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_tallies() {
+        let config = small_config();
+        let parallel = simulate(&config, 200, 42);
+        assert_eq!(parallel, simulate(&config, 200, 42));
+
+        // 1 戦ずつ順に足しても同じ（スレッドの分け方によらない）
+        let sequential = (0..200)
+            .map(|i| simulate_one(&config, 42 + i))
+            .fold(BTreeMap::new(), merge);
+        assert_eq!(parallel, sequential);
+
+        assert_ne!(parallel, simulate(&config, 200, 43));
+    }
+}
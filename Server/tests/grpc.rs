@@ -0,0 +1,362 @@
+// gRPC サービスの結合テスト（同じプロセス内でサーバを立てて tonic のクライアントから呼ぶ）
+
+use battle_server::auth::{Auth, SharedAuth};
+use battle_server::config::ServerConfig;
+use battle_server::grpc::{self, pb};
+use battle_server::lobby::LobbyManager;
+use pb::battle_service_client::BattleServiceClient;
+use pb::match_update::Update;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Channel;
+use tonic::{Code, Request};
+
+struct TestServer {
+    client: BattleServiceClient<Channel>,
+    auth: SharedAuth,
+}
+
+async fn start(config: ServerConfig) -> TestServer {
+    let auth: SharedAuth = Arc::new(Auth::new(b"grpc-test-secret", config.token_ttl));
+    let lobby = config
+        .enable_matchmaking
+        .then(|| LobbyManager::new(config.clone()));
+    let service = grpc::service(lobby, auth.clone(), &config);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let client = BattleServiceClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+    TestServer { client, auth }
+}
+
+fn quick_lobby() -> ServerConfig {
+    ServerConfig {
+        lobby_wait: Duration::from_millis(500),
+        ..ServerConfig::default()
+    }
+}
+
+/// `authorization: Bearer <token>` を付ける
+fn authorized<T>(auth: &Auth, name: &str, message: T) -> Request<T> {
+    let token = auth.issue_token(name).unwrap().token;
+    let mut request = Request::new(message);
+    request.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {}", token).parse().unwrap(),
+    );
+    request
+}
+
+fn join_request(ticket: &str) -> pb::JoinRequest {
+    pb::JoinRequest {
+        name: String::new(),
+        hp: 80,
+        atk: 30,
+        ticket: Some(ticket.to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn battle_with_seed_is_reproducible() {
+    let mut server = start(ServerConfig::default()).await;
+    let request = pb::BattleRequest {
+        characters: vec![pb::CharacterInput {
+            name: "Alice".to_string(),
+            hp: Some(90),
+            atk: None,
+            ..Default::default()
+        }],
+        total_chars: Some(20),
+        npc_hp: Some(pb::StatRange { min: 10, max: 20 }),
+        npc_atk: None,
+        seed: Some(42),
+        return_full_standings: true,
+    };
+
+    let first = server
+        .client
+        .battle(request.clone())
+        .await
+        .unwrap()
+        .into_inner();
+    let second = server.client.battle(request).await.unwrap().into_inner();
+
+    assert_eq!(first, second);
+    assert_eq!(first.seed, 42);
+    assert_eq!(first.total_chars, 20);
+    assert_eq!(first.standings.len(), 20);
+    assert_eq!(first.client_results.len(), 1);
+    assert_eq!(first.client_results[0].name, "Alice");
+}
+
+#[tokio::test]
+async fn battle_rejects_invalid_request() {
+    let mut server = start(ServerConfig::default()).await;
+    let request = pb::BattleRequest {
+        characters: vec![pb::CharacterInput {
+            name: "Alice".to_string(),
+            hp: Some(0),
+            atk: None,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let status = server.client.battle(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert!(status.message().starts_with("invalid_request:"));
+}
+
+#[tokio::test]
+async fn oversized_messages_are_rejected() {
+    let mut server = start(ServerConfig {
+        max_body_bytes: 1024,
+        ..ServerConfig::default()
+    })
+    .await;
+    let request = pb::BattleRequest {
+        characters: vec![pb::CharacterInput {
+            name: "A".repeat(2048),
+            hp: None,
+            atk: None,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let status = server.client.battle(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::OutOfRange);
+}
+
+#[tokio::test]
+async fn calls_are_rate_limited_per_address() {
+    let mut server = start(ServerConfig {
+        grpc_rate_per_ip: 2,
+        ..ServerConfig::default()
+    })
+    .await;
+    let request = pb::BattleRequest {
+        characters: vec![pb::CharacterInput {
+            name: "Alice".to_string(),
+            hp: None,
+            atk: None,
+            ..Default::default()
+        }],
+        total_chars: Some(2),
+        ..Default::default()
+    };
+
+    for _ in 0..2 {
+        server.client.battle(request.clone()).await.unwrap();
+    }
+    let status = server.client.battle(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert!(status.message().starts_with("rate_limited:"));
+}
+
+#[tokio::test]
+async fn disabled_modes_are_unimplemented() {
+    let mut server = start(ServerConfig {
+        enable_matchmaking: false,
+        enable_instant: false,
+        ..ServerConfig::default()
+    })
+    .await;
+
+    let status = server
+        .client
+        .battle(pb::BattleRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+
+    let status = server
+        .client
+        .get_leaderboard(pb::LeaderboardRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+#[tokio::test]
+async fn join_requires_token() {
+    let mut server = start(quick_lobby()).await;
+
+    let status = server.client.join(join_request("t1")).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let mut request = Request::new(join_request("t1"));
+    request
+        .metadata_mut()
+        .insert("authorization", "Bearer not-a-token".parse().unwrap());
+    let status = server.client.join(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn join_watch_and_leaderboard() {
+    let server = start(quick_lobby()).await;
+    let auth = server.auth.clone();
+
+    let mut join_client = server.client.clone();
+    let join_auth = auth.clone();
+    let join = tokio::spawn(async move {
+        join_client
+            .join(authorized(&join_auth, "alice", join_request("alice-1")))
+            .await
+    });
+
+    // Join がロビーに入るまで待ってから購読する
+    let mut watch_client = server.client.clone();
+    let mut stream = loop {
+        match watch_client
+            .watch_match(authorized(
+                &auth,
+                "alice",
+                pb::WatchMatchRequest {
+                    ticket: "alice-1".to_string(),
+                },
+            ))
+            .await
+        {
+            Ok(response) => break response.into_inner(),
+            Err(status) if status.code() == Code::NotFound => {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            Err(status) => panic!("watch failed: {}", status),
+        }
+    };
+
+    let mut updates = Vec::new();
+    while let Some(update) = stream.message().await.unwrap() {
+        updates.push(update.update.unwrap());
+    }
+
+    assert!(matches!(
+        updates[0],
+        Update::Waiting(pb::Waiting { players: 1, .. })
+    ));
+    assert!(matches!(
+        updates[1],
+        Update::Started(pb::Started { players: 1 })
+    ));
+    let Some(Update::Finished(finished)) = updates.last() else {
+        panic!("stream did not end with a result: {:?}", updates);
+    };
+
+    let joined = join.await.unwrap().unwrap().into_inner();
+    assert_eq!(&joined, finished);
+    assert_eq!(joined.name, "alice");
+    assert!(joined.rank >= 1 && joined.rank <= 100);
+
+    let mut client = server.client.clone();
+    let leaderboard = client
+        .get_leaderboard(pb::LeaderboardRequest { limit: 5 })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(leaderboard.entries.len(), 1);
+    assert_eq!(leaderboard.entries[0].name, "alice");
+    assert_eq!(leaderboard.entries[0].matches, 1);
+    assert_eq!(leaderboard.entries[0].best_rank, joined.rank);
+}
+
+#[tokio::test]
+async fn watch_is_limited_to_the_ticket_owner() {
+    let server = start(quick_lobby()).await;
+    let auth = server.auth.clone();
+
+    let mut join_client = server.client.clone();
+    let join_auth = auth.clone();
+    let join = tokio::spawn(async move {
+        join_client
+            .join(authorized(&join_auth, "alice", join_request("alice-2")))
+            .await
+    });
+
+    let mut client = server.client.clone();
+    let status = loop {
+        let status = client
+            .watch_match(authorized(
+                &auth,
+                "bob",
+                pb::WatchMatchRequest {
+                    ticket: "alice-2".to_string(),
+                },
+            ))
+            .await
+            .unwrap_err();
+        if status.code() != Code::NotFound {
+            break status;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    assert_eq!(status.code(), Code::PermissionDenied);
+
+    join.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn squad_join_returns_results_per_character() {
+    let server = start(quick_lobby()).await;
+    let mut client = server.client.clone();
+
+    let member = |name: &str| pb::SquadMember {
+        name: name.to_string(),
+        hp: 80,
+        atk: 30,
+        ..Default::default()
+    };
+
+    // duel は1回の参加で1人まで
+    let status = client
+        .join(authorized(
+            &server.auth,
+            "alice",
+            pb::JoinRequest {
+                mode: pb::GameMode::Duel.into(),
+                squad: vec![member("scout")],
+                ..join_request("alice-3")
+            },
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let joined = client
+        .join(authorized(
+            &server.auth,
+            "alice",
+            pb::JoinRequest {
+                mode: pb::GameMode::Skirmish.into(),
+                squad: vec![member("scout"), member("tank")],
+                ..join_request("alice-4")
+            },
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(joined.name, "alice");
+    let names: Vec<&str> = joined.squad.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["alice/scout", "alice/tank"]);
+
+    // 3人とも別の順位になる
+    let mut ranks: Vec<u32> = joined.squad.iter().map(|r| r.rank).collect();
+    ranks.push(joined.rank);
+    ranks.sort();
+    ranks.dedup();
+    assert_eq!(ranks.len(), 3);
+    assert!(ranks.iter().all(|&r| (1..=20).contains(&r)));
+}
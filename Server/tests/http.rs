@@ -0,0 +1,753 @@
+// HTTP API の結合テスト（axum の Router を tower::ServiceExt::oneshot で直接呼ぶ）
+
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use axum::Router;
+use battle_api::{LobbyInfo, MAX_STAT, PROTOCOL_HEADER};
+use battle_server::auth::{self, Auth, SharedAuth};
+use battle_server::config::{AbandonPolicy, ServerConfig};
+use battle_server::lobby::LobbyManager;
+use battle_server::{app, instant};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+/// main.rs と同じルーター（enable_matchmaking / enable_instant も同じように効く）
+fn full_app(config: ServerConfig) -> (Router, SharedAuth) {
+    let auth: SharedAuth = Arc::new(Auth::new(b"http-test-secret", config.token_ttl));
+    let lobby = config
+        .enable_matchmaking
+        .then(|| LobbyManager::new(config.clone()));
+    (app::router(&config, lobby, auth.clone()), auth)
+}
+
+fn bearer(auth: &Auth, name: &str) -> String {
+    format!("Bearer {}", auth.issue_token(name).unwrap().token)
+}
+
+async fn send_raw(app: Router, request: Request<Body>) -> (StatusCode, HeaderMap, Bytes) {
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, headers, body)
+}
+
+async fn send_with_headers(app: Router, request: Request<Body>) -> (StatusCode, HeaderMap, Value) {
+    let (status, headers, body) = send_raw(app, request).await;
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    (status, headers, body)
+}
+
+async fn send(app: Router, request: Request<Body>) -> (StatusCode, Value) {
+    let (status, _, body) = send_with_headers(app, request).await;
+    (status, body)
+}
+
+fn post_json(uri: &str, body: Value) -> Request<Body> {
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// 422 で details.field に `field` が入っていること
+async fn assert_invalid_field(app: Router, request: Request<Body>, field: &str) {
+    let (status, body) = send(app, request).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    assert_eq!(body["code"], "invalid_request");
+    assert_eq!(body["details"]["field"], field, "{}", body);
+}
+
+// ===== 即時バトルの入力チェック =====
+
+#[tokio::test]
+async fn battle_rejects_stats_above_the_ceiling() {
+    let cases = [
+        (
+            json!({ "name": "a", "hp": MAX_STAT + 1 }),
+            "characters[0].hp",
+        ),
+        (
+            json!({ "name": "a", "atk": MAX_STAT + 1 }),
+            "characters[0].atk",
+        ),
+        (json!({ "name": "a", "hp": i32::MAX }), "characters[0].hp"),
+    ];
+    for (character, field) in cases {
+        let request = post_json("/battle", json!({ "characters": [character] }));
+        assert_invalid_field(instant::routes(&ServerConfig::default()), request, field).await;
+    }
+}
+
+#[tokio::test]
+async fn battle_rejects_npc_ranges_out_of_bounds() {
+    let cases = [
+        (
+            json!({ "npc_hp": { "min": 1, "max": MAX_STAT + 1 } }),
+            "npc_hp",
+        ),
+        (json!({ "npc_atk": { "min": 0, "max": 10 } }), "npc_atk"),
+        (
+            json!({ "npc_atk": { "min": 1, "max": i32::MAX } }),
+            "npc_atk",
+        ),
+    ];
+    for (mut body, field) in cases {
+        body["characters"] = json!([{ "name": "a" }]);
+        assert_invalid_field(
+            instant::routes(&ServerConfig::default()),
+            post_json("/battle", body),
+            field,
+        )
+        .await;
+    }
+}
+
+#[tokio::test]
+async fn battle_accepts_stats_at_the_ceiling() {
+    let body = json!({
+        "characters": [{ "name": "a", "hp": MAX_STAT, "atk": MAX_STAT }],
+        "total_chars": 4,
+        "npc_hp": { "min": MAX_STAT, "max": MAX_STAT },
+        "npc_atk": { "min": 1, "max": MAX_STAT },
+        "seed": 7,
+    });
+    let (status, body) = send(
+        instant::routes(&ServerConfig::default()),
+        post_json("/battle", body),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["total_chars"], 4);
+}
+
+#[tokio::test]
+async fn roster_battle_rejects_stats_above_the_ceiling() {
+    let roster = json!({
+        "version": 1,
+        "characters": [
+            { "name": "a", "hp": 100, "atk": 10 },
+            { "name": "b", "hp": MAX_STAT + 1, "atk": 10 },
+        ],
+    });
+    let request = post_json("/battle/roster", roster);
+    assert_invalid_field(
+        instant::routes(&ServerConfig::default()),
+        request,
+        "characters[1].hp",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn battle_is_rate_limited_per_address() {
+    let app = instant::routes(&ServerConfig {
+        instant_rate_per_ip: 2,
+        ..ServerConfig::default()
+    });
+    let battle = || post_json("/battle", json!({ "characters": [], "total_chars": 10 }));
+    for _ in 0..2 {
+        let (status, body) = send(app.clone(), battle()).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+    assert_rate_limited(app.clone(), battle(), 30).await;
+
+    // /battle/roster も同じ枠を使う
+    let roster = post_json(
+        "/battle/roster",
+        json!({ "characters": [{ "name": "a", "hp": 10, "atk": 1 }] }),
+    );
+    let (status, _) = send(app, roster).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+// ===== /register・/login の回数制限 =====
+
+#[tokio::test]
+async fn login_is_rate_limited_per_address() {
+    let config = ServerConfig {
+        auth_rate_per_ip: 3,
+        ..ServerConfig::default()
+    };
+    let app = auth::routes(
+        Arc::new(Auth::new(b"http-test-secret", config.token_ttl)),
+        &config,
+    );
+    let login = || {
+        post_json(
+            "/login",
+            json!({ "name": "alice", "password": "wrong-password" }),
+        )
+    };
+
+    // 制限までは通常どおり（アカウントが無いので 401）
+    for _ in 0..3 {
+        let (status, _) = send(app.clone(), login()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    let (status, headers, body) = send_with_headers(app.clone(), login()).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "rate_limited");
+    let retry_after: u64 = headers[header::RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=20).contains(&retry_after), "{}", retry_after);
+
+    // /register も同じ枠を使う
+    let register = post_json(
+        "/register",
+        json!({ "name": "bob", "password": "password123" }),
+    );
+    let (status, _) = send(app, register).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+// ===== エラーボディ・プロトコル・形式（/v1 全体） =====
+
+#[tokio::test]
+async fn malformed_json_is_a_400_with_an_error_body() {
+    let (app, _) = full_app(ServerConfig::default());
+    let request = Request::post("/v1/battle")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from("{\"characters\": [}"))
+        .unwrap();
+
+    let (status, body) = send(app, request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "malformed_json");
+    assert!(!body["message"].as_str().unwrap().is_empty());
+    assert_eq!(body["details"]["line"], 1);
+    assert!(body["details"]["column"].as_u64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn wrong_field_type_names_the_field() {
+    let (app, _) = full_app(ServerConfig::default());
+    let body = json!({ "characters": [{ "name": "a", "hp": "lots" }] });
+
+    let (status, body) = send(app, post_json("/v1/battle", body)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "invalid_body");
+    assert_eq!(body["details"]["field"], "characters[0].hp");
+}
+
+#[tokio::test]
+async fn old_protocol_is_426_client_too_old() {
+    let (app, _) = full_app(ServerConfig::default());
+    let request = Request::get("/v1/lobbies")
+        .header(PROTOCOL_HEADER, "0")
+        .body(Body::empty())
+        .unwrap();
+
+    let (status, headers, body) = send_with_headers(app.clone(), request).await;
+    assert_eq!(status, StatusCode::UPGRADE_REQUIRED);
+    assert_eq!(body["code"], "client_too_old");
+    assert_eq!(body["details"]["client_protocol_version"], 0);
+    assert!(headers.contains_key(PROTOCOL_HEADER));
+
+    // /v1 が付く前のパスも同じ
+    let (status, body) = send(app, post_json("/join", json!({}))).await;
+    assert_eq!(status, StatusCode::UPGRADE_REQUIRED);
+    assert_eq!(body["code"], "client_too_old");
+}
+
+#[tokio::test]
+async fn msgpack_is_returned_when_accepted() {
+    let (app, _) = full_app(ServerConfig::default());
+    let body = json!({ "characters": [{ "name": "a" }], "total_chars": 3, "seed": 1 });
+    let mut request = post_json("/v1/battle", body);
+    request
+        .headers_mut()
+        .insert(header::ACCEPT, "application/msgpack".parse().unwrap());
+
+    let (status, headers, body) = send_raw(app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::CONTENT_TYPE], "application/msgpack");
+    assert_eq!(headers[header::VARY], "accept");
+    let result: battle_api::BattleResult = rmp_serde::from_slice(&body).unwrap();
+    assert_eq!(result.seed, 1);
+    assert_eq!(result.total_chars, 3);
+}
+
+// ===== ロビーとチケット =====
+
+fn join_body(ticket: &str) -> Value {
+    json!({ "hp": 80, "atk": 5, "ticket": ticket })
+}
+
+/// 開いているロビーの一覧（誰かが入るまで待つ）
+async fn wait_for_lobby(app: &Router) -> Value {
+    for _ in 0..50 {
+        let request = Request::get("/v1/lobbies").body(Body::empty()).unwrap();
+        let (status, body) = send(app.clone(), request).await;
+        assert_eq!(status, StatusCode::OK);
+        if body.as_array().is_some_and(|l| !l.is_empty()) {
+            return body;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("nobody joined a lobby");
+}
+
+#[tokio::test]
+async fn lobbies_lists_open_lobbies() {
+    let (app, auth) = full_app(ServerConfig {
+        lobby_wait: Duration::from_secs(30),
+        ..ServerConfig::default()
+    });
+    let mut join = post_json("/v1/join", join_body("alice-1"));
+    join.headers_mut().insert(
+        header::AUTHORIZATION,
+        bearer(&auth, "alice").parse().unwrap(),
+    );
+    let waiting = tokio::spawn(send(app.clone(), join));
+
+    let lobbies = wait_for_lobby(&app).await;
+    let lobby = &lobbies[0];
+    let mut keys: Vec<&str> = lobby
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(|k| k.as_str())
+        .collect();
+    keys.sort_unstable();
+    assert_eq!(
+        keys,
+        [
+            "battle_size",
+            "capacity",
+            "id",
+            "max_squad",
+            "mode",
+            "players",
+            "seconds_left"
+        ]
+    );
+    let info: LobbyInfo = serde_json::from_value(lobby.clone()).unwrap();
+    assert_eq!(info.players, 1);
+    assert_eq!(lobby["mode"], "classic");
+    assert!((1..=30).contains(&info.seconds_left));
+
+    waiting.abort();
+}
+
+#[tokio::test]
+async fn only_the_owner_can_cancel_a_ticket() {
+    let (app, auth) = full_app(ServerConfig {
+        lobby_wait: Duration::from_secs(30),
+        ..ServerConfig::default()
+    });
+    let mut join = post_json("/v1/join", join_body("alice-1"));
+    join.headers_mut().insert(
+        header::AUTHORIZATION,
+        bearer(&auth, "alice").parse().unwrap(),
+    );
+    let waiting = tokio::spawn(send(app.clone(), join));
+    wait_for_lobby(&app).await;
+
+    let cancel = |name: &str| {
+        Request::delete("/v1/tickets/alice-1")
+            .header(header::AUTHORIZATION, bearer(&auth, name))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let (status, body) = send(app.clone(), cancel("bob")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "forbidden");
+
+    let (status, _) = send(app.clone(), cancel("alice")).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, body) = waiting.await.unwrap();
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "join_cancelled");
+}
+
+#[tokio::test]
+async fn join_without_a_ticket_cannot_be_cancelled() {
+    let (app, auth) = full_app(ServerConfig {
+        lobby_wait: Duration::from_secs(30),
+        ..ServerConfig::default()
+    });
+    let mut join = post_json("/v1/join", json!({ "hp": 80, "atk": 5 }));
+    join.headers_mut().insert(
+        header::AUTHORIZATION,
+        bearer(&auth, "alice").parse().unwrap(),
+    );
+    let waiting = tokio::spawn(send(app.clone(), join));
+    wait_for_lobby(&app).await;
+
+    // サーバが振った内部用の ID の形でも取り消せない
+    let cancel = Request::delete("/v1/tickets/auto:00000000000000000000000000000000")
+        .header(header::AUTHORIZATION, bearer(&auth, "alice"))
+        .body(Body::empty())
+        .unwrap();
+    let (status, body) = send(app.clone(), cancel).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "not_found");
+
+    let lobbies = wait_for_lobby(&app).await;
+    assert_eq!(lobbies[0]["players"], 1);
+    waiting.abort();
+}
+
+/// ログイン済みの /join（応答は締め切りまで返らないので spawn して使う）
+fn join_as(auth: &Auth, name: &str, body: Value) -> Request<Body> {
+    let mut join = post_json("/v1/join", body);
+    join.headers_mut()
+        .insert(header::AUTHORIZATION, bearer(auth, name).parse().unwrap());
+    join
+}
+
+/// 429 で Retry-After が 1..=`max` 秒であること
+async fn assert_rate_limited(app: Router, request: Request<Body>, max: u64) -> Value {
+    let (status, headers, body) = send_with_headers(app, request).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{}", body);
+    assert_eq!(body["code"], "rate_limited");
+    let retry_after: u64 = headers[header::RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=max).contains(&retry_after), "{}", retry_after);
+    body
+}
+
+#[tokio::test]
+async fn join_is_rate_limited_per_address() {
+    let (app, auth) = full_app(ServerConfig {
+        lobby_wait: Duration::from_secs(30),
+        join_rate_per_ip: 2,
+        ..ServerConfig::default()
+    });
+    let mut waiting = Vec::new();
+    for name in ["alice", "bob"] {
+        let join = join_as(&auth, name, join_body(&format!("{}-1", name)));
+        waiting.push(tokio::spawn(send(app.clone(), join)));
+    }
+    let lobbies = wait_for_lobby(&app).await;
+    assert!(lobbies[0]["players"].as_u64().unwrap() >= 1);
+
+    // アカウントを変えても同じ接続元からは 3 回目で止まる
+    let join = join_as(&auth, "carol", join_body("carol-1"));
+    assert_rate_limited(app.clone(), join, 30).await;
+
+    for task in waiting {
+        task.abort();
+    }
+}
+
+#[tokio::test]
+async fn join_is_refused_when_max_pending_joins_is_reached() {
+    // classic は定員 = max_pending_joins なので、squad 付きで 2 人分入れても始まらない
+    let (app, auth) = full_app(ServerConfig {
+        lobby_wait: Duration::from_secs(30),
+        max_pending_joins: 3,
+        ..ServerConfig::default()
+    });
+    let mut alice = join_body("alice-1");
+    alice["squad"] = json!([{ "name": "second", "hp": 80, "atk": 5 }]);
+    let mut waiting = vec![tokio::spawn(send(
+        app.clone(),
+        join_as(&auth, "alice", alice),
+    ))];
+    wait_for_lobby(&app).await;
+    // 別の mode のロビーも同じ上限に数える（duel は 2 人で始まるので 1 人だけ）
+    let mut bob = join_body("bob-1");
+    bob["mode"] = json!("duel");
+    waiting.push(tokio::spawn(send(app.clone(), join_as(&auth, "bob", bob))));
+    for _ in 0..50 {
+        let request = Request::get("/v1/lobbies").body(Body::empty()).unwrap();
+        let (_, lobbies) = send(app.clone(), request).await;
+        if lobbies.as_array().unwrap().len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let join = join_as(&auth, "carol", join_body("carol-1"));
+    let body = assert_rate_limited(app.clone(), join, 90).await;
+    assert!(
+        body["message"]
+            .as_str()
+            .unwrap()
+            .contains("lobbies are full"),
+        "{}",
+        body
+    );
+
+    for task in waiting {
+        task.abort();
+    }
+}
+
+// ===== 切断した参加者（abandon_policy） =====
+
+fn get(uri: &str) -> Request<Body> {
+    Request::get(uri).body(Body::empty()).unwrap()
+}
+
+/// alice と bob が待機し、alice の /join だけ締め切り前に切断する。
+/// bob に届いたマッチの (MatchRecord, BattleLog) を返す
+async fn match_with_a_disconnected_player(policy: AbandonPolicy) -> (Value, Value) {
+    let (app, auth) = full_app(ServerConfig {
+        lobby_wait: Duration::from_secs(1),
+        abandon_policy: policy,
+        ..ServerConfig::default()
+    });
+    let alice = tokio::spawn(send(
+        app.clone(),
+        join_as(&auth, "alice", join_body("alice-1")),
+    ));
+    let bob = tokio::spawn(send(app.clone(), join_as(&auth, "bob", join_body("bob-1"))));
+    for _ in 0..50 {
+        let lobbies = wait_for_lobby(&app).await;
+        if lobbies[0]["players"] == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // リクエストごと drop されるので、ロビー側の送信口が閉じる
+    alice.abort();
+    assert!(alice.await.unwrap_err().is_cancelled());
+
+    let (status, result) = bob.await.unwrap();
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    let id = result["match_id"].as_u64().unwrap();
+
+    let (status, matches) = send(app.clone(), get("/v1/matches")).await;
+    assert_eq!(status, StatusCode::OK);
+    let record = matches
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["id"] == id)
+        .cloned()
+        .unwrap_or_else(|| panic!("match {} is not listed: {}", id, matches));
+    let (status, log) = send(app, get(&format!("/v1/matches/{}/log", id))).await;
+    assert_eq!(status, StatusCode::OK);
+    (record, log)
+}
+
+fn log_fighter<'a>(log: &'a Value, name: &str) -> Option<&'a Value> {
+    log["fighters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["name"] == name)
+}
+
+#[tokio::test]
+async fn disconnected_player_is_removed_from_the_match() {
+    let (record, log) = match_with_a_disconnected_player(AbandonPolicy::Remove).await;
+    assert_eq!(record["players"], json!(["bob"]));
+    assert_eq!(
+        record["abandoned"],
+        json!([{ "name": "alice", "stage": "lobby", "handled_as": "remove" }])
+    );
+    assert!(log_fighter(&log, "alice").is_none());
+    assert_eq!(log_fighter(&log, "bob").unwrap()["is_client"], true);
+}
+
+#[tokio::test]
+async fn disconnected_player_fights_on_as_an_npc() {
+    let (record, log) = match_with_a_disconnected_player(AbandonPolicy::ConvertToNpc).await;
+    assert_eq!(record["players"], json!(["bob"]));
+    assert_eq!(
+        record["abandoned"],
+        json!([{ "name": "alice", "stage": "lobby", "handled_as": "npc" }])
+    );
+    // 同じステータスのまま NPC として戦う（クラス補正後の hp 80 / atk 5）
+    let alice = log_fighter(&log, "alice").expect("alice stays in the battle");
+    assert_eq!(alice["is_client"], false);
+    assert_eq!(
+        (alice["hp"].clone(), alice["atk"].clone()),
+        (json!(80), json!(5))
+    );
+    assert_eq!(
+        record["npc_count"],
+        log["fighters"].as_array().unwrap().len() - 1
+    );
+}
+
+// ===== 無効にした API（enable_matchmaking / enable_instant） =====
+
+async fn capabilities(app: &Router) -> (Vec<String>, Value) {
+    let (status, info) = send(app.clone(), get("/v1/version")).await;
+    assert_eq!(status, StatusCode::OK);
+    let capabilities = serde_json::from_value(info["capabilities"].clone()).unwrap();
+    (capabilities, info["stat_budget"].clone())
+}
+
+async fn assert_status(app: &Router, request: Request<Body>, expected: StatusCode) {
+    let uri = request.uri().to_string();
+    let (status, body) = send(app.clone(), request).await;
+    assert_eq!(status, expected, "{}: {}", uri, body);
+}
+
+fn roster_request() -> Request<Body> {
+    post_json(
+        "/v1/battle/roster",
+        json!({
+            "version": 1,
+            "characters": [
+                { "name": "a", "hp": 100, "atk": 10 },
+                { "name": "b", "hp": 100, "atk": 10 },
+            ],
+        }),
+    )
+}
+
+#[tokio::test]
+async fn disabled_matchmaking_is_not_routed() {
+    let (app, auth) = full_app(ServerConfig {
+        enable_matchmaking: false,
+        ..ServerConfig::default()
+    });
+
+    let credentials = json!({ "name": "alice", "password": "password123" });
+    let not_found = [
+        join_as(&auth, "alice", join_body("alice-1")),
+        get("/v1/lobbies"),
+        get("/v1/matches"),
+        get("/v1/matches/1/log"),
+        Request::delete("/v1/tickets/alice-1")
+            .header(header::AUTHORIZATION, bearer(&auth, "alice"))
+            .body(Body::empty())
+            .unwrap(),
+        post_json("/v1/register", credentials.clone()),
+        post_json("/v1/login", credentials),
+    ];
+    for request in not_found {
+        assert_status(&app, request, StatusCode::NOT_FOUND).await;
+    }
+    let battle = post_json("/v1/battle", json!({ "characters": [], "total_chars": 4 }));
+    assert_status(&app, battle, StatusCode::OK).await;
+    assert_status(&app, roster_request(), StatusCode::OK).await;
+
+    let (capabilities, stat_budget) = capabilities(&app).await;
+    for missing in ["matchmaking", "lobbies", "squad", "verify", "battle_log"] {
+        assert!(
+            !capabilities.iter().any(|c| c == missing),
+            "{:?}",
+            capabilities
+        );
+    }
+    assert!(capabilities.iter().any(|c| c == "instant"));
+    assert!(capabilities.iter().any(|c| c == "roster"));
+    assert_eq!(stat_budget, Value::Null);
+}
+
+#[tokio::test]
+async fn disabled_instant_battles_are_not_routed() {
+    let (app, _) = full_app(ServerConfig {
+        enable_instant: false,
+        ..ServerConfig::default()
+    });
+
+    let battle = post_json("/v1/battle", json!({ "characters": [], "total_chars": 4 }));
+    assert_status(&app, battle, StatusCode::NOT_FOUND).await;
+    assert_status(&app, roster_request(), StatusCode::NOT_FOUND).await;
+    assert_status(&app, get("/v1/lobbies"), StatusCode::OK).await;
+
+    let (capabilities, stat_budget) = capabilities(&app).await;
+    assert!(!capabilities.iter().any(|c| c == "instant" || c == "roster"));
+    assert!(capabilities.iter().any(|c| c == "matchmaking"));
+    assert_eq!(stat_budget["points"], 40);
+}
+
+// ===== /openapi.json =====
+
+#[cfg(feature = "openapi")]
+async fn openapi_paths(config: ServerConfig) -> Vec<String> {
+    let (app, _) = full_app(config);
+    let (status, doc) = send(app, get("/openapi.json")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        doc["openapi"].as_str().unwrap().starts_with("3."),
+        "{}",
+        doc
+    );
+    doc["paths"].as_object().unwrap().keys().cloned().collect()
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn openapi_lists_the_enabled_paths() {
+    const MATCHMAKING: [&str; 7] = [
+        "/v1/register",
+        "/v1/login",
+        "/v1/join",
+        "/v1/tickets/{id}",
+        "/v1/lobbies",
+        "/v1/matches",
+        "/v1/matches/{id}/log",
+    ];
+    const INSTANT: [&str; 2] = ["/v1/battle", "/v1/battle/roster"];
+
+    let mut expected: Vec<&str> = ["/v1/version"]
+        .into_iter()
+        .chain(MATCHMAKING)
+        .chain(INSTANT)
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(openapi_paths(ServerConfig::default()).await, expected);
+
+    let paths = openapi_paths(ServerConfig {
+        enable_matchmaking: false,
+        ..ServerConfig::default()
+    })
+    .await;
+    assert_eq!(paths, ["/v1/battle", "/v1/battle/roster", "/v1/version"]);
+
+    let paths = openapi_paths(ServerConfig {
+        enable_instant: false,
+        ..ServerConfig::default()
+    })
+    .await;
+    assert!(
+        INSTANT.iter().all(|p| !paths.iter().any(|q| q == p)),
+        "{:?}",
+        paths
+    );
+    assert_eq!(paths.len(), 1 + MATCHMAKING.len());
+}
+
+// ===== 知らない enum の値 =====
+
+#[tokio::test]
+async fn unknown_enum_values_are_rejected_on_input() {
+    let (app, auth) = full_app(ServerConfig::default());
+    let squad = |class: &str| json!([{ "name": "second", "hp": 80, "atk": 5, "class": class }]);
+    let cases = [
+        ("mode", json!({ "mode": "battle_royale" })),
+        ("class", json!({ "class": "healer" })),
+        ("targeting", json!({ "targeting": "sneakiest" })),
+        ("squad[0].class", json!({ "squad": squad("healer") })),
+    ];
+    for (field, extra) in cases {
+        let mut body = join_body("alice-1");
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        assert_invalid_field(app.clone(), join_as(&auth, "alice", body), field).await;
+    }
+
+    let battle = json!({
+        "characters": [{ "name": "a", "targeting": "sneakiest" }],
+        "total_chars": 4,
+    });
+    assert_invalid_field(
+        app,
+        post_json("/v1/battle", battle),
+        "characters[0].targeting",
+    )
+    .await;
+}